@@ -0,0 +1,125 @@
+//! Measures the cost of emitting signposts through the macros, the derive macro and `OsLog`.
+//!
+//! Each benchmark runs against a log whose sink is always disabled, one whose sink is always
+//! enabled but discards signposts, and a log emitting through os_signpost. The enabled sink
+//! measures the overhead of the crate itself, without the cost of recording in the system;
+//! the os_signpost log is only enabled while Instruments records the process.
+//!
+//! To run: cargo bench --bench emission
+
+use std::sync::{Arc, OnceLock};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use signpost::{categories, signpost, Emission, OsLog, SignpostId, SignpostSink};
+
+/// A sink that reports a fixed enabled state and discards signposts.
+#[derive(Debug)]
+struct NullSink(bool);
+
+impl SignpostSink for NullSink {
+    fn enabled(&self, _log: &OsLog) -> bool {
+        self.0
+    }
+
+    fn emit(&self, _log: &OsLog, emission: &Emission<'_>) {
+        std::hint::black_box(emission);
+    }
+}
+
+/// The logs to compare, by benchmark name.
+fn logs() -> &'static [(&'static str, OsLog)] {
+    static LOGS: OnceLock<Vec<(&'static str, OsLog)>> = OnceLock::new();
+    LOGS.get_or_init(|| {
+        let log = || OsLog::new("bench.emission".to_string(), categories::POINTS_OF_INTEREST);
+        vec![
+            ("disabled", log().with_sink(Arc::new(NullSink(false)))),
+            ("enabled", log().with_sink(Arc::new(NullSink(true)))),
+            ("os_signpost", log()),
+        ]
+    })
+}
+
+fn event(c: &mut Criterion) {
+    let mut group = c.benchmark_group("event");
+    for (name, log) in logs() {
+        group.bench_function(*name, |b| b.iter(|| signpost::event!(log, "event")));
+    }
+    group.finish();
+}
+
+fn event_cstr(c: &mut Criterion) {
+    let mut group = c.benchmark_group("event_cstr");
+    for (name, log) in logs() {
+        group.bench_function(*name, |b| {
+            b.iter(|| log.event_cstr(SignpostId::next(), c"event"))
+        });
+    }
+    group.finish();
+}
+
+fn event_with_message(c: &mut Criterion) {
+    let mut group = c.benchmark_group("event_with_message");
+    for (name, log) in logs() {
+        group.bench_function(*name, |b| {
+            b.iter(|| signpost::event_with_message!(log, "event", "message"))
+        });
+    }
+    group.finish();
+}
+
+fn interval(c: &mut Criterion) {
+    let mut group = c.benchmark_group("interval");
+    for (name, log) in logs() {
+        group.bench_function(*name, |b| {
+            b.iter(|| drop(signpost::interval!(log, "interval")))
+        });
+    }
+    group.finish();
+}
+
+fn interval_reused_id(c: &mut Criterion) {
+    let mut group = c.benchmark_group("interval_reused_id");
+    for (name, log) in logs() {
+        let id = SignpostId::next();
+        group.bench_function(*name, |b| b.iter(|| drop(log.interval(id, "interval"))));
+    }
+    group.finish();
+}
+
+fn derive(c: &mut Criterion) {
+    fn log(index: usize) -> &'static OsLog {
+        &logs()[index].1
+    }
+
+    #[signpost(log = log(0))]
+    fn disabled(value: u64) -> u64 {
+        std::hint::black_box(value)
+    }
+
+    #[signpost(log = log(1))]
+    fn enabled(value: u64) -> u64 {
+        std::hint::black_box(value)
+    }
+
+    #[signpost(log = log(2))]
+    fn os_signpost(value: u64) -> u64 {
+        std::hint::black_box(value)
+    }
+
+    let mut group = c.benchmark_group("derive");
+    group.bench_function("disabled", |b| b.iter(|| disabled(1)));
+    group.bench_function("enabled", |b| b.iter(|| enabled(1)));
+    group.bench_function("os_signpost", |b| b.iter(|| os_signpost(1)));
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    event,
+    event_cstr,
+    event_with_message,
+    interval,
+    interval_reused_id,
+    derive
+);
+criterion_main!(benches);
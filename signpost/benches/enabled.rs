@@ -0,0 +1,36 @@
+//! Compares querying the enabled state on every call against the cached state.
+//!
+//! To run: cargo bench --bench enabled
+
+use std::hint::black_box;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use signpost::{categories, OsLog};
+
+fn enabled(c: &mut Criterion) {
+    let live = OsLog::new("bench.enabled".to_string(), categories::POINTS_OF_INTEREST);
+    let cached = OsLog::new("bench.enabled".to_string(), categories::POINTS_OF_INTEREST)
+        .with_enabled_cache(Duration::from_millis(100));
+
+    let mut group = c.benchmark_group("enabled");
+    group.bench_function("live", |b| b.iter(|| black_box(live.enabled())));
+    group.bench_function("cached", |b| b.iter(|| black_box(cached.enabled())));
+    group.finish();
+}
+
+fn interval(c: &mut Criterion) {
+    let live = OsLog::new("bench.interval".to_string(), categories::POINTS_OF_INTEREST);
+    let cached = OsLog::new("bench.interval".to_string(), categories::POINTS_OF_INTEREST)
+        .with_enabled_cache(Duration::from_millis(100));
+
+    let mut group = c.benchmark_group("interval");
+    for (name, log) in [("live", &live), ("cached", &cached)] {
+        let id = signpost::SignpostId::generate(log);
+        group.bench_function(name, |b| b.iter(|| drop(log.interval(id, "interval"))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, enabled, interval);
+criterion_main!(benches);
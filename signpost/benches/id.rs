@@ -0,0 +1,22 @@
+//! Compares generating signpost IDs through the system against the process-local counter.
+//!
+//! To run: cargo bench --bench id
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use signpost::{categories, OsLog, SignpostId};
+
+fn id(c: &mut Criterion) {
+    let log = OsLog::new("bench.id".to_string(), categories::POINTS_OF_INTEREST);
+
+    let mut group = c.benchmark_group("id");
+    group.bench_function("generate", |b| {
+        b.iter(|| black_box(SignpostId::generate(&log)))
+    });
+    group.bench_function("next", |b| b.iter(|| black_box(SignpostId::next())));
+    group.finish();
+}
+
+criterion_group!(benches, id);
+criterion_main!(benches);
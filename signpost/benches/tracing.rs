@@ -0,0 +1,32 @@
+//! Measures the cost of a span round trip through the `TracingSubscriber` layer.
+//!
+//! Compares entering and closing a span on a registry without the layer against one with
+//! it. The layer only emits signposts while Instruments records the process.
+//!
+//! To run: cargo bench --bench tracing --features tracing
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use signpost::{categories, Signpost, TracingSubscriber};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Registry;
+
+fn span(c: &mut Criterion) {
+    let _ = Signpost::configure("bench.tracing", categories::POINTS_OF_INTEREST);
+
+    let mut group = c.benchmark_group("span");
+    group.bench_function("registry", |b| {
+        tracing::subscriber::with_default(Registry::default(), || {
+            b.iter(|| tracing::info_span!("span", value = 1).in_scope(|| {}))
+        })
+    });
+    group.bench_function("signpost", |b| {
+        let subscriber = Registry::default().with(TracingSubscriber::new());
+        tracing::subscriber::with_default(subscriber, || {
+            b.iter(|| tracing::info_span!("span", value = 1).in_scope(|| {}))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, span);
+criterion_main!(benches);
@@ -2,11 +2,18 @@ use std::env;
 use std::path::PathBuf;
 
 fn main() {
+    // Off Apple platforms the crate compiles against a no-op stand-in for the bindings.
+    if env::var("CARGO_CFG_TARGET_VENDOR").as_deref() != Ok("apple") {
+        return;
+    }
+
     let bindings = bindgen::Builder::default()
         .header_contents(
             "temporary.h",
             "#include <os/log.h>\n#include <os/signpost.h>",
         )
+        // Refer to `core` types, so the bindings also compile without `std`.
+        .use_core()
         // Explicitly allowlist functions and variables.
         .allowlist_function("os_log_create")
         .allowlist_function("os_signpost_enabled")
@@ -4,7 +4,8 @@ use signpost::{categories, Signpost};
 
 fn main() {
     // Configure signpost at startup once.
-    Signpost::configure("dev.vortex", categories::POINTS_OF_INTEREST);
+    Signpost::configure("dev.vortex", categories::POINTS_OF_INTEREST)
+        .expect("Signpost already configured");
 
     // Call signpost instrumented functions.
     let data = data::load();
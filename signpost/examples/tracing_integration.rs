@@ -12,7 +12,8 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Registry
 
 fn main() {
     // Configure signpost at startup once.
-    Signpost::configure("dev.vortex", categories::POINTS_OF_INTEREST);
+    Signpost::configure("dev.vortex", categories::POINTS_OF_INTEREST)
+        .expect("Signpost already configured");
 
     Registry::default()
         .with(signpost::TracingSubscriber::new())
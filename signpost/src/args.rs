@@ -0,0 +1,393 @@
+//! Typed format arguments for signpost messages.
+//!
+//! Arguments are marshalled into the os_log format buffer layout decoded by Instruments:
+//! a two byte header (summary flags and argument count) followed by one item per argument,
+//! each consisting of a descriptor byte, a size byte and the argument data.
+
+use std::ffi::{CStr, CString};
+use std::fmt::{self, Write};
+
+/// Header flag set when any argument is private.
+const HDR_FLAG_HAS_PRIVATE: u8 = 0x1;
+/// Header flag set when any argument is passed by pointer.
+const HDR_FLAG_HAS_NON_SCALAR: u8 = 0x2;
+
+/// Descriptor flag for arguments annotated with `{private}`.
+const CMD_FLAG_PRIVATE: u8 = 0x1;
+/// Descriptor flag for arguments annotated with `{public}`.
+const CMD_FLAG_PUBLIC: u8 = 0x2;
+
+/// Descriptor type for scalar arguments stored inline.
+const CMD_TYPE_SCALAR: u8 = 0;
+/// Descriptor type for C string arguments stored by pointer.
+const CMD_TYPE_STRING: u8 = 2;
+
+/// Size of a single item: descriptor byte, size byte and eight bytes of data.
+const ITEM_LEN: usize = 10;
+/// Maximum number of arguments os_log decodes from a format buffer (`OS_LOG_FMT_MAX_CMDS`).
+const MAX_ARGS: usize = 48;
+
+// Dart SDK for reference on how to set up the format buffer:
+// https://github.com/dart-lang/sdk/blob/3e2d3bc77fa8bb5139b869e9b3a5357b5487df18/runtime/vm/timeline_macos.cc#L34C1-L34C34
+const FORMAT_BUFFER_LEN: usize = 64;
+
+#[derive(Clone, Copy)]
+#[repr(align(16))]
+struct AlignedBuffer {
+    data: [u8; FORMAT_BUFFER_LEN],
+}
+
+const EMPTY_BUFFER: AlignedBuffer = AlignedBuffer {
+    data: [0; FORMAT_BUFFER_LEN],
+};
+
+/// Backing storage of a format buffer.
+///
+/// Every emission encodes into its own buffer, so concurrent emissions never share memory.
+/// Buffers live on the stack unless the arguments need more than [`FORMAT_BUFFER_LEN`] bytes.
+enum Storage {
+    Inline(AlignedBuffer),
+    /// Contiguous chunks, which keep the alignment of the inline buffer.
+    Heap(Vec<AlignedBuffer>),
+}
+
+impl Storage {
+    /// Storage for at least `len` bytes.
+    fn with_len(len: usize) -> Self {
+        if len <= FORMAT_BUFFER_LEN {
+            Storage::Inline(EMPTY_BUFFER)
+        } else {
+            Storage::Heap(vec![EMPTY_BUFFER; len.div_ceil(FORMAT_BUFFER_LEN)])
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Storage::Inline(buffer) => &buffer.data,
+            // SAFETY: the chunks are contiguous byte arrays without padding.
+            Storage::Heap(chunks) => unsafe {
+                std::slice::from_raw_parts(chunks.as_ptr().cast(), chunks.len() * FORMAT_BUFFER_LEN)
+            },
+        }
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        match self {
+            Storage::Inline(buffer) => &mut buffer.data,
+            // SAFETY: the chunks are contiguous byte arrays without padding.
+            Storage::Heap(chunks) => unsafe {
+                std::slice::from_raw_parts_mut(
+                    chunks.as_mut_ptr().cast(),
+                    chunks.len() * FORMAT_BUFFER_LEN,
+                )
+            },
+        }
+    }
+}
+
+/// A typed argument for a signpost format string.
+///
+/// Each argument corresponds to one conversion specifier in the format string, e.g.
+/// `c"%{public}lld items in %{public}s"` expects an `I64` followed by a `Str`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignpostArg<'a> {
+    /// A signed integer, formatted with `%lld`.
+    I64(i64),
+    /// An unsigned integer, formatted with `%llu`.
+    U64(u64),
+    /// A floating point number, formatted with `%f`.
+    F64(f64),
+    /// A string, formatted with `%s`.
+    Str(&'a str),
+}
+
+impl fmt::Display for SignpostArg<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignpostArg::I64(value) => value.fmt(f),
+            SignpostArg::U64(value) => value.fmt(f),
+            SignpostArg::F64(value) => value.fmt(f),
+            SignpostArg::Str(value) => value.fmt(f),
+        }
+    }
+}
+
+/// Formats fields as space separated `key=value` pairs.
+///
+/// os_log decodes format strings from the binary image, so field names can't be part of
+/// a format string built at runtime. Instead the fields are rendered into the message,
+/// where custom Instruments packages can parse them into columns with patterns like
+/// `size=%d`.
+pub(crate) fn format_fields(fields: &[(&str, SignpostArg<'_>)]) -> String {
+    let mut message = String::new();
+    for (key, value) in fields {
+        if !message.is_empty() {
+            message.push(' ');
+        }
+        let _ = write!(message, "{key}={value}");
+    }
+    message
+}
+
+/// Substitutes the arguments into the conversion specifiers of a format string.
+///
+/// Privacy annotations, flags, width and precision are ignored, so `%{public}5.2f` renders
+/// like `{}`. Missing arguments render as nothing.
+pub(crate) fn render(format: &CStr, args: &[SignpostArg<'_>]) -> String {
+    let format = format.to_string_lossy();
+    let mut message = String::with_capacity(format.len());
+    let mut args = args.iter();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            message.push(c);
+            continue;
+        }
+        if chars.next_if_eq(&'%').is_some() {
+            message.push('%');
+            continue;
+        }
+        if chars.next_if_eq(&'{').is_some() {
+            chars.by_ref().take_while(|&c| c != '}').for_each(drop);
+        }
+        // Skip flags, width, precision and length modifiers up to the conversion.
+        chars
+            .by_ref()
+            .find(|c| c.is_ascii_alphabetic() && !"hljztLq".contains(*c));
+        match args.next() {
+            // Like os_log shows interior NUL bytes of strings, see `crate::to_cstring`.
+            Some(SignpostArg::Str(value)) if value.contains('\0') => {
+                message.push_str(&value.replace('\0', "\u{FFFD}"));
+            }
+            Some(arg) => {
+                let _ = write!(message, "{arg}");
+            }
+            None => {}
+        }
+    }
+
+    message
+}
+
+/// An encoded os_log format buffer along with the strings it points to.
+pub(crate) struct FormatBuffer {
+    storage: Storage,
+    // String arguments are referenced by pointer from the buffer.
+    _strings: Vec<CString>,
+}
+
+impl FormatBuffer {
+    /// Encodes the arguments for the given format string.
+    ///
+    /// The buffer grows beyond [`FORMAT_BUFFER_LEN`] bytes when the arguments need it.
+    /// Arguments beyond the [`MAX_ARGS`] os_log decodes are dropped.
+    pub(crate) fn encode(format: Option<&CStr>, args: &[SignpostArg<'_>]) -> Self {
+        let privacy = format.map(privacy_flags).unwrap_or_default();
+        let args = &args[..args.len().min(MAX_ARGS)];
+        let mut storage = Storage::with_len(2 + args.len() * ITEM_LEN);
+        let data = storage.bytes_mut();
+        let mut strings = Vec::new();
+        let mut summary = 0;
+        let mut offset = 2;
+
+        for (idx, arg) in args.iter().enumerate() {
+            let flags = privacy.get(idx).copied().unwrap_or_default();
+            let (kind, value) = match arg {
+                SignpostArg::I64(value) => (CMD_TYPE_SCALAR, value.to_ne_bytes()),
+                SignpostArg::U64(value) => (CMD_TYPE_SCALAR, value.to_ne_bytes()),
+                SignpostArg::F64(value) => (CMD_TYPE_SCALAR, value.to_ne_bytes()),
+                SignpostArg::Str(value) => {
+                    let cstr = crate::to_cstring(value);
+                    let ptr = cstr.as_ptr() as u64;
+                    strings.push(cstr);
+                    (CMD_TYPE_STRING, ptr.to_ne_bytes())
+                }
+            };
+
+            data[offset] = (kind << 4) | flags;
+            data[offset + 1] = value.len() as u8;
+            data[offset + 2..offset + ITEM_LEN].copy_from_slice(&value);
+            offset += ITEM_LEN;
+
+            if kind != CMD_TYPE_SCALAR {
+                summary |= HDR_FLAG_HAS_NON_SCALAR;
+            }
+            if flags & CMD_FLAG_PRIVATE != 0 {
+                summary |= HDR_FLAG_HAS_PRIVATE;
+            }
+        }
+
+        data[0] = summary;
+        data[1] = args.len() as u8;
+
+        Self {
+            storage,
+            _strings: strings,
+        }
+    }
+
+    /// Encodes a single public C string argument for [`crate::MESSAGE_FORMAT`].
+    ///
+    /// The string is referenced without a copy, so it must outlive the emission.
+    pub(crate) fn encode_message(message: &CStr) -> Self {
+        let mut storage = Storage::with_len(2 + ITEM_LEN);
+        let data = storage.bytes_mut();
+        data[0] = HDR_FLAG_HAS_NON_SCALAR;
+        data[1] = 1;
+        data[2] = (CMD_TYPE_STRING << 4) | CMD_FLAG_PUBLIC;
+        data[3] = 8;
+        data[4..2 + ITEM_LEN].copy_from_slice(&(message.as_ptr() as u64).to_ne_bytes());
+
+        Self {
+            storage,
+            _strings: Vec::new(),
+        }
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.storage.bytes_mut().as_mut_ptr()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.storage.bytes().len()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn bytes(&self) -> &[u8] {
+        self.storage.bytes()
+    }
+}
+
+/// Extracts the privacy annotation of every conversion specifier in a format string.
+fn privacy_flags(format: &CStr) -> Vec<u8> {
+    let mut flags = Vec::new();
+    let mut bytes = format.to_bytes().iter().peekable();
+
+    while let Some(&byte) = bytes.next() {
+        if byte != b'%' || bytes.next_if_eq(&&b'%').is_some() {
+            continue;
+        }
+
+        let mut flag = 0;
+        if bytes.next_if_eq(&&b'{').is_some() {
+            let annotation: Vec<u8> = bytes
+                .by_ref()
+                .take_while(|&&byte| byte != b'}')
+                .copied()
+                .collect();
+            for token in annotation.split(|&byte| byte == b',') {
+                match token.trim_ascii() {
+                    b"public" => flag |= CMD_FLAG_PUBLIC,
+                    b"private" => flag |= CMD_FLAG_PRIVATE,
+                    _ => {}
+                }
+            }
+        }
+        flags.push(flag);
+    }
+
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_privacy_flags() {
+        assert_eq!(
+            privacy_flags(c"%{public}s %% %{private}lld %f %{ public, name=x }llu"),
+            vec![CMD_FLAG_PUBLIC, CMD_FLAG_PRIVATE, 0, CMD_FLAG_PUBLIC]
+        );
+    }
+
+    #[test]
+    fn test_format_fields() {
+        let fields = [
+            ("size", SignpostArg::U64(1024)),
+            ("delta", SignpostArg::I64(-1)),
+            ("ratio", SignpostArg::F64(0.5)),
+            ("status", SignpostArg::Str("ok")),
+        ];
+        assert_eq!(
+            format_fields(&fields),
+            "size=1024 delta=-1 ratio=0.5 status=ok"
+        );
+        assert_eq!(format_fields(&[]), "");
+    }
+
+    #[test]
+    fn test_render() {
+        let args = [
+            SignpostArg::I64(-1),
+            SignpostArg::F64(0.5),
+            SignpostArg::Str("text"),
+        ];
+        assert_eq!(
+            render(c"%{public}lld, %5.2f%% and %{private, name=x}s", &args),
+            "-1, 0.5% and text"
+        );
+        assert_eq!(render(c"plain", &args), "plain");
+        assert_eq!(render(c"%s %s", &[]), " ");
+        assert_eq!(render(c"%s", &[SignpostArg::Str("a\0b")]), "a\u{FFFD}b");
+    }
+
+    #[test]
+    fn test_encode() {
+        let buffer = FormatBuffer::encode(
+            Some(c"%{public}lld %{private}s"),
+            &[SignpostArg::I64(-1), SignpostArg::Str("text")],
+        );
+        let bytes = buffer.bytes();
+
+        assert_eq!(bytes[0], HDR_FLAG_HAS_PRIVATE | HDR_FLAG_HAS_NON_SCALAR);
+        assert_eq!(bytes[1], 2);
+        assert_eq!(bytes[2], CMD_FLAG_PUBLIC);
+        assert_eq!(bytes[3], 8);
+        assert_eq!(bytes[4..12], (-1i64).to_ne_bytes());
+        assert_eq!(bytes[12], (CMD_TYPE_STRING << 4) | CMD_FLAG_PRIVATE);
+        assert_eq!(bytes[13], 8);
+    }
+
+    #[test]
+    fn test_encode_message() {
+        let message = c"message";
+        let buffer = FormatBuffer::encode_message(message);
+        let encoded = FormatBuffer::encode(Some(crate::MESSAGE_FORMAT), &[SignpostArg::Str("x")]);
+
+        // Same layout as an encoded string argument, pointing to the message itself.
+        assert_eq!(buffer.bytes()[..4], encoded.bytes()[..4]);
+        assert_eq!(
+            buffer.bytes()[4..12],
+            (message.as_ptr() as u64).to_ne_bytes()
+        );
+        assert!(buffer._strings.is_empty());
+    }
+
+    #[test]
+    fn test_encode_interior_nul() {
+        let buffer = FormatBuffer::encode(Some(c"%{public}s"), &[SignpostArg::Str("a\0b")]);
+        assert_eq!(buffer._strings[0].to_str(), Ok("a\u{FFFD}b"));
+    }
+
+    #[test]
+    fn test_encode_grows_buffer() {
+        let buffer = FormatBuffer::encode(None, &[SignpostArg::U64(1); 6]);
+        assert_eq!(buffer.len(), FORMAT_BUFFER_LEN);
+
+        let buffer = FormatBuffer::encode(None, &[SignpostArg::U64(7); 10]);
+        let bytes = buffer.bytes();
+        assert_eq!(buffer.len(), 2 * FORMAT_BUFFER_LEN);
+        assert_eq!(bytes.as_ptr() as usize % 16, 0);
+        assert_eq!(bytes[1], 10);
+        assert_eq!(bytes[94..102], 7u64.to_ne_bytes());
+    }
+
+    #[test]
+    fn test_encode_drops_overflowing_args() {
+        let args = [SignpostArg::U64(1); MAX_ARGS + 2];
+        let buffer = FormatBuffer::encode(None, &args);
+        assert_eq!(buffer.bytes()[1] as usize, MAX_ARGS);
+    }
+}
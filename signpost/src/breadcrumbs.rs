@@ -0,0 +1,88 @@
+//! Names of the intervals open on the current thread, to name nested intervals after them.
+//!
+//! Each entry is the full name of an interval, so the name of a nested interval is its
+//! parent's followed by its own, e.g. `outer > inner`.
+
+use std::cell::RefCell;
+use std::thread::{self, ThreadId};
+
+/// Separator between the names of nested intervals.
+const SEPARATOR: &str = " > ";
+
+thread_local! {
+    static STACK: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// An entry on the stack of the thread it was pushed on, removed with [`Breadcrumb::pop`].
+#[derive(Debug)]
+pub(crate) struct Breadcrumb {
+    thread: ThreadId,
+    depth: usize,
+}
+
+impl Breadcrumb {
+    /// Removes the entry and any left above it, e.g. by intervals leaked with `mem::forget`.
+    ///
+    /// Entries popped on another thread, e.g. of an interval moved into a spawned thread,
+    /// are left for the next entry below them to remove.
+    pub(crate) fn pop(self) {
+        if thread::current().id() != self.thread {
+            return;
+        }
+        // The stack is already gone while thread locals are destroyed.
+        let _ = STACK.try_with(|stack| stack.borrow_mut().truncate(self.depth));
+    }
+}
+
+/// The name of an interval nested in the innermost interval open on this thread.
+pub(crate) fn compose(name: &str) -> String {
+    STACK
+        .try_with(|stack| match stack.borrow().last() {
+            Some(parent) => format!("{parent}{SEPARATOR}{name}"),
+            None => name.to_string(),
+        })
+        .unwrap_or_else(|_| name.to_string())
+}
+
+/// Pushes the full name of an interval that began on this thread.
+pub(crate) fn push(name: String) -> Breadcrumb {
+    let depth = STACK
+        .try_with(|stack| {
+            let mut stack = stack.borrow_mut();
+            stack.push(name);
+            stack.len() - 1
+        })
+        .unwrap_or(0);
+    Breadcrumb {
+        thread: thread::current().id(),
+        depth,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breadcrumbs() {
+        assert_eq!(compose("outer"), "outer");
+        let outer = push(compose("outer"));
+        assert_eq!(compose("inner"), "outer > inner");
+        let inner = push(compose("inner"));
+        assert_eq!(compose("leaf"), "outer > inner > leaf");
+        inner.pop();
+        assert_eq!(compose("sibling"), "outer > sibling");
+
+        // Popping an outer entry removes the ones left above it.
+        let _leaked = push(compose("leaked"));
+        outer.pop();
+        assert_eq!(compose("outer"), "outer");
+
+        // Entries are only popped on their own thread.
+        let entry = push(compose("outer"));
+        thread::spawn(move || entry.pop()).join().unwrap();
+        assert_eq!(compose("inner"), "outer > inner");
+        push(String::new()).pop();
+        STACK.with(|stack| stack.borrow_mut().clear());
+    }
+}
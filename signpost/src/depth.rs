@@ -0,0 +1,60 @@
+//! Nesting depth of the intervals open on the current thread, to limit deep recursion.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::{self, ThreadId};
+
+/// Intervals nested deeper than this are suppressed, unlimited if `usize::MAX`.
+static MAX_DEPTH: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Sets the maximum depth, see `Signpost::set_max_interval_depth`.
+pub(crate) fn set_max(max_depth: usize) {
+    MAX_DEPTH.store(max_depth, Ordering::Relaxed);
+}
+
+/// The depth of an interval on the thread it began on, restored with [`Depth::exit`].
+#[derive(Debug)]
+pub(crate) struct Depth {
+    thread: ThreadId,
+    depth: usize,
+}
+
+impl Depth {
+    /// Counts an interval beginning on this thread, unless the depth is unlimited.
+    pub(crate) fn enter() -> Option<Self> {
+        let max_depth = MAX_DEPTH.load(Ordering::Relaxed);
+        if max_depth == usize::MAX {
+            return None;
+        }
+        let depth = DEPTH
+            .try_with(|depth| {
+                let current = depth.get();
+                depth.set(current + 1);
+                current
+            })
+            .ok()?;
+        Some(Self {
+            thread: thread::current().id(),
+            depth,
+        })
+    }
+
+    /// Whether the interval is nested too deep to be emitted.
+    pub(crate) fn suppressed(&self) -> bool {
+        self.depth >= MAX_DEPTH.load(Ordering::Relaxed)
+    }
+
+    /// Restores the depth from before the interval began.
+    ///
+    /// Like [`crate::breadcrumbs::Breadcrumb::pop`], intervals ending on another thread
+    /// leave the depth for an interval they're nested in to restore.
+    pub(crate) fn exit(self) {
+        if thread::current().id() == self.thread {
+            let _ = DEPTH.try_with(|depth| depth.set(self.depth));
+        }
+    }
+}
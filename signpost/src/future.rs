@@ -0,0 +1,151 @@
+//! Future adapter for signpost intervals.
+//!
+//! Provides [`SignpostFutureExt`] to instrument arbitrary futures without the
+//! `#[signpost]` attribute, e.g. when composing futures manually.
+
+use crate::{current_logger, OsLog, SignpostId, SignpostInterval};
+use pin_project_lite::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+pin_project! {
+    /// A future wrapped in a signpost interval.
+    ///
+    /// The interval begins when the future is first polled and ends when it completes
+    /// or when the wrapper is dropped before completion.
+    pub struct Instrumented<F> {
+        #[pin]
+        future: F,
+        // The current logger of the first poll if `None`.
+        logger: Option<&'static OsLog>,
+        // Taken when the interval begins on first poll.
+        name: Option<String>,
+        interval: Option<SignpostInterval<'static>>,
+    }
+}
+
+impl<F: Future> Future for Instrumented<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        if let Some(name) = this.name.take() {
+            let logger = this.logger.unwrap_or_else(current_logger);
+            *this.interval = Some(logger.interval(SignpostId::generate(logger), name));
+        }
+
+        let output = ready!(this.future.poll(cx));
+        if let Some(interval) = this.interval.take() {
+            interval.end();
+        }
+
+        Poll::Ready(output)
+    }
+}
+
+/// Extension trait to instrument futures with signpost intervals.
+pub trait SignpostFutureExt: Future + Sized {
+    /// Wrap the future in a signpost interval on the current logger.
+    ///
+    /// The logger is resolved when the future is first polled, so scoped and task
+    /// subsystems active while it runs apply, like for the signpost macros.
+    ///
+    /// # Usage
+    ///
+    /// ```ignore
+    /// use signpost::SignpostFutureExt;
+    ///
+    /// let response = client.get(url).send().signpost_interval("fetch").await;
+    /// ```
+    fn signpost_interval<T: Into<String>>(self, name: T) -> Instrumented<Self> {
+        Instrumented {
+            future: self,
+            logger: None,
+            name: Some(name.into()),
+            interval: None,
+        }
+    }
+
+    /// Wrap the future in a signpost interval on the given logger.
+    fn signpost_interval_on<T: Into<String>>(
+        self,
+        logger: &'static OsLog,
+        name: T,
+    ) -> Instrumented<Self> {
+        Instrumented {
+            future: self,
+            logger: Some(logger),
+            name: Some(name.into()),
+            interval: None,
+        }
+    }
+}
+
+impl<F: Future> SignpostFutureExt for F {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::categories;
+    use std::pin::pin;
+    use std::task::Waker;
+    use std::time::{Duration, Instant};
+
+    /// A future that completes once its deadline has passed.
+    struct Timer {
+        deadline: Instant,
+    }
+
+    impl Future for Timer {
+        type Output = Duration;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let now = Instant::now();
+            if now >= self.deadline {
+                Poll::Ready(now - self.deadline)
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn test_instrumented_future() {
+        static LOG: std::sync::OnceLock<OsLog> = std::sync::OnceLock::new();
+        let log = LOG
+            .get_or_init(|| OsLog::new("test_future".to_string(), categories::POINTS_OF_INTEREST));
+
+        let timer = Timer {
+            deadline: Instant::now() + Duration::from_millis(10),
+        };
+        let mut future = pin!(timer.signpost_interval_on(log, "timer"));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(future.interval.is_none());
+
+        let mut polls = 0;
+        while future.as_mut().poll(&mut cx).is_pending() {
+            // The interval stays open while the future is pending.
+            assert!(future.interval.is_some());
+            polls += 1;
+        }
+
+        assert!(polls > 0);
+        assert!(future.interval.is_none());
+        assert!(future.name.is_none());
+    }
+
+    #[test]
+    fn test_current_logger_on_first_poll() {
+        let mut future = pin!(std::future::pending::<()>().signpost_interval("pending"));
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let poll =
+            crate::Signpost::with_subsystem("test_future.scoped", || future.as_mut().poll(&mut cx));
+        assert!(poll.is_pending());
+        let interval = future.interval.as_ref().unwrap();
+        assert_eq!(interval.log.subsystem(), "test_future.scoped");
+    }
+}
@@ -0,0 +1,94 @@
+//! Iterator adapter for signpost intervals.
+//!
+//! Provides [`SignpostIterExt`] to measure the processing of each item of an iterator
+//! without opening an interval in the loop body.
+
+use crate::{current_logger, OsLog, SignpostId, SignpostInterval};
+
+/// An iterator that opens a signpost interval for each item it yields.
+///
+/// The interval of an item begins when it is yielded and ends when the next item is
+/// requested, so it covers the processing of the item by the consumer. The interval of
+/// the last item ends when the iterator is exhausted or dropped.
+pub struct SignpostEach<I> {
+    iter: I,
+    logger: &'static OsLog,
+    name: &'static str,
+    interval: Option<SignpostInterval<'static>>,
+}
+
+impl<I: Iterator> Iterator for SignpostEach<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // End the interval of the previous item before the next one begins.
+        if let Some(interval) = self.interval.take() {
+            interval.end();
+        }
+
+        let item = self.iter.next()?;
+        let logger = self.logger;
+        self.interval = Some(logger.interval(SignpostId::next(), self.name));
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Extension trait to instrument iterators with a signpost interval per item.
+pub trait SignpostIterExt: Iterator + Sized {
+    /// Open a signpost interval named `name` around the processing of each item.
+    ///
+    /// # Usage
+    ///
+    /// ```ignore
+    /// use signpost::SignpostIterExt;
+    ///
+    /// for item in items.iter().signpost_each("process") {
+    ///     process(item);
+    /// }
+    /// ```
+    fn signpost_each(self, name: &'static str) -> SignpostEach<Self> {
+        self.signpost_each_on(current_logger(), name)
+    }
+
+    /// Open a signpost interval on the given logger around the processing of each item.
+    fn signpost_each_on(self, logger: &'static OsLog, name: &'static str) -> SignpostEach<Self> {
+        SignpostEach {
+            iter: self,
+            logger,
+            name,
+            interval: None,
+        }
+    }
+}
+
+impl<I: Iterator> SignpostIterExt for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::categories;
+    use std::sync::OnceLock;
+
+    #[test]
+    fn test_signpost_each() {
+        static LOG: OnceLock<OsLog> = OnceLock::new();
+        let log =
+            LOG.get_or_init(|| OsLog::new("test_iter".to_string(), categories::POINTS_OF_INTEREST));
+
+        let mut iter = [1, 2].into_iter().signpost_each_on(log, "item");
+        assert!(iter.interval.is_none());
+
+        assert_eq!(iter.next(), Some(1));
+        let first = iter.interval.as_ref().unwrap().id();
+        assert_eq!(iter.next(), Some(2));
+        // Each item gets its own interval.
+        assert_ne!(iter.interval.as_ref().unwrap().id(), first);
+
+        assert_eq!(iter.next(), None);
+        assert!(iter.interval.is_none());
+    }
+}
@@ -1,3 +1,4 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
 //! Signpost library for macOS.
@@ -18,18 +19,71 @@
 //! Signpost interval begin and end matching can have different scopes:
 //! - Thread-wide: Matching is restricted to single threads
 //! - Process-wide: Matching is restricted to a single process (default)
-//! - System-wide: Matching can span across processes
+//! - System-wide: Matching can span across processes, see `OsLog::begin_interval_raw`
+//!
+//! ## Compiling Out Signposts
+//! With the `disabled` feature every signpost is compiled out: the macros expand to nothing,
+//! `#[signpost]` leaves functions unchanged and `OsLog::enabled()` is always `false`, so
+//! the optimizer removes all emission code.
+//!
+//! ## Unconfigured Use
+//! Signposts used before [`Signpost::configure`] are emitted to the `signpost.unconfigured`
+//! subsystem instead of panicking. With the `warn-unconfigured` feature, debug builds also
+//! print a one-time warning to stderr, so a missing configuration is noticed in development.
+//!
+//! ## Tokio Tasks
+//! With the `tokio` feature, `Signpost::spawn_with_subsystem` spawns a task whose
+//! signposts go to its own subsystem, e.g. per tenant of a server. Unlike
+//! [`Signpost::scoped_subsystem`], the subsystem follows the task across `.await` points.
+//!
+//! ## Console Output
+//! With the `console` feature, signposts are also printed to stderr, e.g.
+//! `[signpost    0.000012] BEGIN my_crate::load (id=0x1)`, to follow signpost activity
+//! where Instruments isn't available, like on CI, over SSH or on non-Apple targets. Printing
+//! can be turned off at runtime with `ConsoleSink::set_enabled`.
+//!
+//! ## `no_std`
+//! Without the default `std` feature only the data types [`SignpostId`], [`SignpostKind`]
+//! and [`SignpostError`] are available, e.g. to pass IDs through `no_std` code that hands
+//! them to an instrumented host. Loggers, intervals and the macros require `std`.
+//!
+//! ## Platform Support
+//! On non-Apple targets the crate compiles against a no-op implementation: `enabled()`
+//! always returns `false` and no signposts are emitted, so instrumentation can stay in
+//! shared code without `#[cfg]` gates at every call site.
 
+#[cfg(feature = "std")]
+pub use args::SignpostArg;
+#[cfg(feature = "std")]
+use args::{format_fields, FormatBuffer};
+#[cfg(feature = "std")]
 pub use signpost_derive::signpost;
 
+#[cfg(feature = "std")]
 use std::{
-    ffi::{c_void, CStr},
+    cell::RefCell,
+    collections::HashMap,
+    ffi::{c_void, CStr, CString},
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    marker::PhantomData,
+    num::NonZeroUsize,
+    ops::Deref,
     sync::{
-        atomic::{AtomicPtr, Ordering},
-        OnceLock,
+        atomic::{AtomicBool, AtomicPtr, AtomicU64, Ordering},
+        Arc, LazyLock, Mutex, OnceLock, PoisonError, RwLock,
     },
+    time::{Duration, Instant},
 };
 
+#[cfg(feature = "std")]
+mod args;
+#[cfg(feature = "std")]
+mod breadcrumbs;
+#[cfg(feature = "std")]
+mod depth;
+
+#[cfg(target_vendor = "apple")]
 mod sys {
     #![allow(non_upper_case_globals)]
     #![allow(non_camel_case_types)]
@@ -46,7 +100,71 @@ mod sys {
     };
 }
 
+/// No-op stand-ins for the os_signpost API on platforms without it.
+///
+/// Mirrors the subset of the generated bindings used by this crate: signposts are never
+/// enabled and every emission is discarded.
+#[cfg(not(target_vendor = "apple"))]
+mod sys {
+    #![allow(non_upper_case_globals)]
+    #![allow(non_camel_case_types)]
+    #![allow(dead_code)]
+
+    use core::ffi::{c_char, c_void};
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    pub enum os_log_s {}
+    pub type os_log_t = *mut os_log_s;
+    pub type os_signpost_id_t = u64;
+    pub type os_signpost_type_t = u8;
+
+    pub const SIGNPOST_TYPE_EVENT: os_signpost_type_t = 0;
+    pub const SIGNPOST_TYPE_INTERVAL_BEGIN: os_signpost_type_t = 1;
+    pub const SIGNPOST_TYPE_INTERVAL_END: os_signpost_type_t = 2;
+
+    pub const OS_LOG_CATEGORY_POINTS_OF_INTEREST: &[u8; 17] = b"PointsOfInterest\0";
+    pub const OS_LOG_CATEGORY_DYNAMIC_TRACING: &[u8; 15] = b"DynamicTracing\0";
+    pub const OS_LOG_CATEGORY_DYNAMIC_STACK_TRACING: &[u8; 20] = b"DynamicStackTracing\0";
+
+    pub static mut __dso_handle: u8 = 0;
+
+    pub unsafe fn os_log_create(_subsystem: *const c_char, _category: *const c_char) -> os_log_t {
+        core::ptr::null_mut()
+    }
+
+    pub unsafe fn os_signpost_enabled(_log: os_log_t) -> bool {
+        false
+    }
+
+    pub unsafe fn os_signpost_id_generate(_log: os_log_t) -> os_signpost_id_t {
+        // IDs are still unique so that ID based logic behaves as on Apple platforms.
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub unsafe fn os_signpost_id_make_with_pointer(
+        _log: os_log_t,
+        ptr: *const c_void,
+    ) -> os_signpost_id_t {
+        ptr as os_signpost_id_t
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn _os_signpost_emit_with_name_impl(
+        _dso: *mut c_void,
+        _log: os_log_t,
+        _type: os_signpost_type_t,
+        _spid: os_signpost_id_t,
+        _name: *const c_char,
+        _format: *const c_char,
+        _buf: *mut u8,
+        _size: u32,
+    ) {
+    }
+}
+
 /// Predefined log categories for different types of signpost instrumentation.
+#[cfg(feature = "std")]
 pub mod categories {
     use crate::sys;
     use std::ffi::CStr;
@@ -66,9 +184,37 @@ pub mod categories {
 
     /// Use this category for signposts that should capture user backtraces. This behavior is
     /// more expensive than regular signposts, so it will only be active when a performance
-    /// tool like Instruments is actively recording.
+    /// tool like Instruments is actively recording. See [`OsLog::event_with_backtrace`].
+    ///
+    /// [`OsLog::event_with_backtrace`]: crate::OsLog::event_with_backtrace
     pub const DYNAMIC_STACK_TRACING: &CStr =
         unsafe { &*(sys::OS_LOG_CATEGORY_DYNAMIC_STACK_TRACING as *const [u8] as *const CStr) };
+
+    /// Creates a `&'static CStr` for a custom category from a string literal.
+    ///
+    /// Signposts in a custom category are grouped separately in Instruments. The category
+    /// is checked at compile time and must not contain NUL bytes.
+    ///
+    /// # Examples
+    /// ```
+    /// use signpost::{categories, OsLog};
+    ///
+    /// let log = OsLog::new("com.example.app".to_string(), categories::custom!("networking"));
+    /// ```
+    pub use crate::__custom_category as custom;
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __custom_category {
+    ($category:literal) => {{
+        const CATEGORY: &::std::ffi::CStr =
+            match ::std::ffi::CStr::from_bytes_with_nul(concat!($category, "\0").as_bytes()) {
+                Ok(category) => category,
+                Err(_) => panic!("category must not contain NUL bytes"),
+            };
+        CATEGORY
+    }};
 }
 
 /// Errors that can occur when working with signposts.
@@ -82,19 +228,46 @@ pub enum SignpostError {
 
     /// Signpost ID is invalid or uses a reserved value.
     InvalidId,
+
+    /// The signpost system has already been configured.
+    AlreadyConfigured,
+
+    /// The subsystem string is not a valid C string.
+    InvalidSubsystem,
+
+    /// The value is not a valid `os_signpost_type_t`.
+    InvalidKind,
 }
 
-impl std::fmt::Display for SignpostError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for SignpostError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             SignpostError::NotConfigured => write!(f, "Signpost not initialized"),
             SignpostError::InvalidScope => write!(f, "Invalid scope for operation"),
             SignpostError::InvalidId => write!(f, "Invalid signpost ID"),
+            SignpostError::AlreadyConfigured => write!(f, "Signpost already configured"),
+            SignpostError::InvalidSubsystem => write!(f, "Invalid signpost subsystem"),
+            SignpostError::InvalidKind => write!(f, "Invalid signpost type"),
         }
     }
 }
 
-impl std::error::Error for SignpostError {}
+impl core::error::Error for SignpostError {}
+
+/// Reserved signpost ID value `OS_SIGNPOST_ID_NULL`.
+const SIGNPOST_ID_NULL: u64 = 0;
+
+/// Reserved signpost ID value `OS_SIGNPOST_ID_INVALID`.
+const SIGNPOST_ID_INVALID: u64 = !0;
+
+/// Maps the reserved signpost ID values to valid IDs.
+const fn fold_reserved(id: u64) -> u64 {
+    match id {
+        SIGNPOST_ID_NULL => 1,
+        SIGNPOST_ID_INVALID => SIGNPOST_ID_INVALID - 1,
+        id => id,
+    }
+}
 
 /// A unique identifier for signpost intervals and events.
 ///
@@ -113,10 +286,24 @@ impl SignpostId {
     ///
     /// # Returns
     /// A valid `SignpostId`.
+    #[cfg(feature = "std")]
     pub fn generate(log: &OsLog) -> Self {
         Self(unsafe { sys::os_signpost_id_generate(log.get()) })
     }
 
+    /// Returns the next ID of a process-local counter, without calling into the system.
+    ///
+    /// This is cheaper than [`SignpostId::generate`] for high-frequency signposts. The IDs
+    /// are only unique within the current process, so don't use them with
+    /// [`SignpostScope::System`] logs, and don't mix them with generated IDs for intervals
+    /// with the same log and name.
+    pub fn next() -> Self {
+        use core::sync::atomic::{AtomicU64, Ordering};
+
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        Self(fold_reserved(NEXT_ID.fetch_add(1, Ordering::Relaxed)))
+    }
+
     /// Creates a signpost ID from a pointer value.
     ///
     /// This function mangles the pointer to create a valid signpost ID, including removing
@@ -135,25 +322,57 @@ impl SignpostId {
     ///
     /// # Note
     /// This approach is not applicable to signposts that span process boundaries.
+    #[cfg(feature = "std")]
     pub fn from_pointer<T>(log: &OsLog, ptr: *const T) -> Result<Self, SignpostError> {
+        if log.scope() == SignpostScope::System {
+            return Err(SignpostError::InvalidScope);
+        }
+
         let id = unsafe { sys::os_signpost_id_make_with_pointer(log.get(), ptr as *const c_void) };
         Ok(Self(id))
     }
 
+    /// Creates a signpost ID by hashing a key, e.g. a request UUID or transaction ID.
+    ///
+    /// The same key always maps to the same ID for a given log, so begin and end signposts
+    /// can be correlated through the logical key instead of passing the `SignpostId` around.
+    /// Hashes that collide with the reserved values are folded to valid IDs.
+    ///
+    /// # Parameters
+    /// - `log`: Log handle the ID is used with, whose subsystem and category are hashed
+    ///   along with the key
+    /// - `value`: The key identifying the interval
+    ///
+    /// # Note
+    /// Distinct keys can map to the same ID, although that is unlikely. IDs are only stable
+    /// for binaries built with the same Rust version.
+    #[cfg(feature = "std")]
+    pub fn from_hash<H: Hash + ?Sized>(log: &OsLog, value: &H) -> Self {
+        let mut hasher = DefaultHasher::new();
+        log.subsystem().hash(&mut hasher);
+        log.category().hash(&mut hasher);
+        value.hash(&mut hasher);
+        Self(fold_reserved(hasher.finish()))
+    }
+
     /// Creates a signpost ID from a raw uint64_t value.
     ///
     /// This allows you to use any existing 64-bit value as a signpost ID, as long as
     /// it's not one of the reserved values (0 or ~0). Use this when you have an existing
-    /// unique identifier that you want to use for signpost tracking.
-    ///
-    /// # Safety
-    /// The caller must ensure that the provided value is unique within the matching scope
-    /// and is not one of the reserved values (OS_SIGNPOST_ID_NULL or OS_SIGNPOST_ID_INVALID).
+    /// unique identifier that you want to use for signpost tracking. The caller must
+    /// ensure that the provided value is unique within the matching scope.
     ///
     /// # Parameters
     /// - `id`: A 64-bit value to use as the signpost ID
-    pub const fn from_raw(id: u64) -> Self {
-        Self(id)
+    ///
+    /// # Returns
+    /// - `Ok(SignpostId)`: A valid signpost ID
+    /// - `Err(SignpostError::InvalidId)`: If `id` is OS_SIGNPOST_ID_NULL or OS_SIGNPOST_ID_INVALID
+    pub const fn from_raw(id: u64) -> Result<Self, SignpostError> {
+        match id {
+            SIGNPOST_ID_NULL | SIGNPOST_ID_INVALID => Err(SignpostError::InvalidId),
+            id => Ok(Self(id)),
+        }
     }
 
     /// Returns the raw uint64_t value of this signpost ID.
@@ -164,9 +383,102 @@ impl SignpostId {
     }
 }
 
+/// Name of the sentinel event emitted by [`OsLog::flush`].
+#[cfg(feature = "std")]
+pub const FLUSH_EVENT: &str = "signpost.flush";
+
+/// Default maximum length of signpost names in bytes, see [`OsLog::with_max_name_len`].
+#[cfg(feature = "std")]
+pub const DEFAULT_MAX_NAME_LEN: usize = 1024;
+
+/// Marker appended to truncated signpost names.
+#[cfg(feature = "std")]
+const ELLIPSIS: &str = "\u{2026}";
+
+/// Truncates `name` to at most `max_len` bytes on a char boundary, ending it with an ellipsis.
+#[cfg(feature = "std")]
+fn truncate_name(name: &str, max_len: usize) -> std::borrow::Cow<'_, str> {
+    if name.len() <= max_len {
+        return std::borrow::Cow::Borrowed(name);
+    }
+    let (budget, ellipsis) = match max_len.checked_sub(ELLIPSIS.len()) {
+        Some(budget) => (budget, ELLIPSIS),
+        None => (max_len, ""),
+    };
+    let end = (0..=budget)
+        .rev()
+        .find(|&end| name.is_char_boundary(end))
+        .unwrap_or(0);
+    std::borrow::Cow::Owned(format!("{}{ellipsis}", &name[..end]))
+}
+
+/// Converts a string to a C string, replacing interior NUL bytes with U+FFFD.
+#[cfg(feature = "std")]
+pub(crate) fn to_cstring(value: &str) -> CString {
+    CString::new(value).unwrap_or_else(|_| {
+        CString::new(value.replace('\0', "\u{FFFD}")).expect("NUL bytes were replaced")
+    })
+}
+
+/// Formats the ID as `0x` prefixed hexadecimal, as shown in Instruments.
+impl core::fmt::Display for SignpostId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl core::fmt::LowerHex for SignpostId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+/// Parses hexadecimal IDs with an optional `0x` prefix, as produced by `Display`.
+impl core::str::FromStr for SignpostId {
+    type Err = SignpostError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix("0x").unwrap_or(s);
+        let id = u64::from_str_radix(digits, 16).map_err(|_| SignpostError::InvalidId)?;
+        Self::from_raw(id)
+    }
+}
+
+/// Serializes the ID as its raw `u64` value.
+#[cfg(feature = "serde")]
+impl serde::Serialize for SignpostId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+/// Deserializes the ID from its raw `u64` value, rejecting the reserved values.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SignpostId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = <u64 as serde::Deserialize>::deserialize(deserializer)?;
+        Self::from_raw(id).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Format string used to emit plain string messages.
+#[cfg(feature = "std")]
+pub(crate) const MESSAGE_FORMAT: &CStr = c"%{public}s";
+
+/// Format string used to emit numeric measurements.
+#[cfg(feature = "std")]
+const MEASURE_FORMAT: &CStr = c"%{public}f";
+
+/// Format string used to emit plain string messages that are redacted unless the
+/// system is configured to reveal private data.
+#[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+#[cfg(feature = "std")]
+pub(crate) const PRIVATE_MESSAGE_FORMAT: &CStr = c"%{private}s";
+
 /// Signpost type for different kinds of signpost emissions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
-pub(crate) enum SignpostType {
+pub enum SignpostKind {
     /// A signpost event marking a single point in time
     Event = sys::SIGNPOST_TYPE_EVENT,
     /// The beginning of a signpost interval
@@ -175,6 +487,47 @@ pub(crate) enum SignpostType {
     IntervalEnd = sys::SIGNPOST_TYPE_INTERVAL_END,
 }
 
+/// The raw `os_signpost_type_t` value.
+impl From<SignpostKind> for u8 {
+    fn from(kind: SignpostKind) -> Self {
+        match kind {
+            SignpostKind::Event => sys::SIGNPOST_TYPE_EVENT,
+            SignpostKind::IntervalBegin => sys::SIGNPOST_TYPE_INTERVAL_BEGIN,
+            SignpostKind::IntervalEnd => sys::SIGNPOST_TYPE_INTERVAL_END,
+        }
+    }
+}
+
+/// Converts a raw `os_signpost_type_t` value, failing with `SignpostError::InvalidKind`.
+impl TryFrom<u8> for SignpostKind {
+    type Error = SignpostError;
+
+    fn try_from(value: u8) -> Result<Self, SignpostError> {
+        match value {
+            sys::SIGNPOST_TYPE_EVENT => Ok(SignpostKind::Event),
+            sys::SIGNPOST_TYPE_INTERVAL_BEGIN => Ok(SignpostKind::IntervalBegin),
+            sys::SIGNPOST_TYPE_INTERVAL_END => Ok(SignpostKind::IntervalEnd),
+            _ => Err(SignpostError::InvalidKind),
+        }
+    }
+}
+
+/// Matching scope for signpost interval begin and end pairs.
+///
+/// The scope determines across which boundaries Instruments will match a begin signpost
+/// with its corresponding end signpost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg(feature = "std")]
+pub enum SignpostScope {
+    /// Matching is restricted to a single thread.
+    Thread,
+    /// Matching is restricted to a single process.
+    #[default]
+    Process,
+    /// Matching can span across processes.
+    System,
+}
+
 /// A logger for a specific subsystem and category.
 ///
 /// `OsLog` represents a configured logging destination for signposts. Each logger
@@ -185,393 +538,3896 @@ pub(crate) enum SignpostType {
 /// Loggers are created through the configuration system using `configure()` and should
 /// be reused rather than created repeatedly for the same subsystem/category combination.
 ///
+/// # Strings
+/// Names and messages are passed to os_signpost as C strings. Interior NUL bytes, which C
+/// strings can't represent, are replaced with U+FFFD REPLACEMENT CHARACTER.
+///
+/// # Sharing
+/// Cloning an `OsLog` is cheap: clones share the underlying `os_log_t` handle, which is
+/// created once on first use, as well as the enabled cache. `OsLog` is `Send` and `Sync`,
+/// since os_log handles can be used concurrently from any thread. Settings applied to a
+/// clone afterwards, like [`OsLog::with_scope`], only affect that clone.
+///
 /// # Examples
 /// ```ignore
-/// use signpost::{OsLog, categories};
+/// use signpost::{OsLog, SignpostScope, categories};
 ///
 /// // Create a logger for high-level events
-/// let log = OsLog::new("com.myapp", categories::POINTS_OF_INTEREST);
+/// let log = OsLog::points_of_interest("com.myapp");
 ///
 /// // Create a logger with custom scope
-/// let log = OsLog::new("com.myapp.network", categories::DYNAMIC_TRACING)
+/// let log = OsLog::new("com.myapp.network".to_string(), categories::DYNAMIC_TRACING)
 ///     .with_scope(SignpostScope::Thread);
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg(feature = "std")]
 pub struct OsLog {
+    inner: Arc<LogHandle>,
+    scope: SignpostScope,
+    enabled_cache: Option<Arc<EnabledCache>>,
+    /// Destination of emitted signposts, os_signpost itself if `None`.
+    sink: Option<Arc<dyn SignpostSink>>,
+    /// Names longer than this many bytes are truncated.
+    max_name_len: usize,
+    /// Image signposts are attributed to, the one containing this crate if `None`.
+    dso_handle: Option<NonZeroUsize>,
+    /// Intervals lasting longer emit a slow event, see [`OsLog::with_slow_threshold`].
+    slow_threshold: Option<Duration>,
+}
+
+/// The lazily created `os_log_t` handle shared by clones of an `OsLog`.
+#[derive(Debug)]
+#[cfg(feature = "std")]
+struct LogHandle {
     subsystem: String,
     category: &'static CStr,
     handle: AtomicPtr<sys::os_log_s>,
     init: std::sync::Once,
+    /// IDs and names of the intervals in flight, to detect overlapping intervals sharing both.
+    #[cfg(all(feature = "debug_checks", debug_assertions))]
+    in_flight: Mutex<std::collections::HashSet<(SignpostId, String)>>,
 }
 
-impl OsLog {
-    /// Create a new logger for the given subsystem and category
-    pub fn new(subsystem: String, category: &'static CStr) -> Self {
-        Self {
-            subsystem,
-            category,
-            handle: AtomicPtr::new(std::ptr::null_mut()),
-            init: std::sync::Once::new(),
-        }
-    }
+/// Builder for an [`OsLog`].
+///
+/// Unlike [`OsLog::new`], which fails lazily on first use, the subsystem is validated when
+/// the logger is built.
+///
+/// # Examples
+/// ```ignore
+/// use signpost::{OsLog, categories};
+///
+/// let log = OsLog::builder("com.myapp.network")
+///     .category(categories::DYNAMIC_TRACING)
+///     .build()?;
+/// ```
+#[derive(Debug)]
+#[cfg(feature = "std")]
+pub struct OsLogBuilder {
+    subsystem: String,
+    category: &'static CStr,
+    scope: SignpostScope,
+    enabled_cache: Option<Duration>,
+    max_name_len: usize,
+}
 
-    /// Check if signpost logging is enabled for this logger
-    pub fn enabled(&self) -> bool {
-        let handle = self.get();
-        unsafe { sys::os_signpost_enabled(handle) }
+#[cfg(feature = "std")]
+impl OsLogBuilder {
+    /// Set the category, [`categories::POINTS_OF_INTEREST`] by default.
+    pub fn category(mut self, category: &'static CStr) -> Self {
+        self.category = category;
+        self
     }
 
-    /// Emit a simple event (point in time)
-    pub fn event<T: AsRef<str>>(&self, id: SignpostId, name: T) {
-        self.emit(id, name.as_ref(), None, SignpostType::Event);
+    /// Set the matching scope, see [`OsLog::with_scope`].
+    pub fn scope(mut self, scope: SignpostScope) -> Self {
+        self.scope = scope;
+        self
     }
 
-    /// Emit an event with a formatted message
-    pub fn event_with_message<T1: AsRef<str>, T2: AsRef<str>>(
-        &self,
-        id: SignpostId,
-        name: T1,
-        message: T2,
-    ) {
-        self.emit(
-            id,
-            name.as_ref(),
-            Some(message.as_ref()),
-            SignpostType::Event,
-        );
+    /// Cache the enabled state, see [`OsLog::with_enabled_cache`].
+    pub fn enabled_cache(mut self, refresh: Duration) -> Self {
+        self.enabled_cache = Some(refresh);
+        self
     }
 
-    /// Start a signpost interval
-    pub fn interval<T: AsRef<str>>(&self, id: SignpostId, name: T) -> SignpostInterval<'_> {
-        SignpostInterval::new(self, id, name.as_ref(), None)
+    /// Truncate longer names, see [`OsLog::with_max_name_len`].
+    pub fn max_name_len(mut self, max_len: usize) -> Self {
+        self.max_name_len = max_len;
+        self
     }
 
-    /// Start a signpost interval with a message
-    pub fn interval_with_message<T1: AsRef<str>, T2: AsRef<str>>(
-        &self,
-        id: SignpostId,
-        name: T1,
-        message: T2,
-    ) -> SignpostInterval<'_> {
-        SignpostInterval::new(self, id, name.as_ref(), Some(message.as_ref()))
+    /// Build the logger.
+    ///
+    /// # Returns
+    /// - `Ok(OsLog)`: The configured logger
+    /// - `Err(SignpostError::InvalidSubsystem)`: If the subsystem is empty or contains a NUL byte
+    pub fn build(self) -> Result<OsLog, SignpostError> {
+        validate_subsystem(&self.subsystem)?;
+
+        let log = OsLog::new(self.subsystem, self.category)
+            .with_scope(self.scope)
+            .with_max_name_len(self.max_name_len);
+        Ok(match self.enabled_cache {
+            Some(refresh) => log.with_enabled_cache(refresh),
+            None => log,
+        })
     }
+}
 
-    /// Centralized signpost emission function
-    pub(crate) fn emit(
-        &self,
-        id: SignpostId,
-        name: &str,
-        message: Option<&str>,
-        signpost_type: SignpostType,
-    ) {
-        if !self.enabled() {
-            return;
-        }
+/// Cached result of `os_signpost_enabled`, refreshed lazily once it is older than `refresh`.
+#[derive(Debug)]
+#[cfg(feature = "std")]
+struct EnabledCache {
+    refresh: Duration,
+    enabled: AtomicBool,
+    /// Time of the last refresh in nanoseconds since `monotonic_nanos` was first called.
+    refreshed_at: AtomicU64,
+}
 
-        let name_cstr = std::ffi::CString::new(name).unwrap_or_default();
-        let message_cstr = message.map(|msg| std::ffi::CString::new(msg).unwrap_or_default());
+#[cfg(feature = "std")]
+impl EnabledCache {
+    /// Marker for a cache that has never been refreshed.
+    const NEVER: u64 = u64::MAX;
 
-        let os_signpost_type = match signpost_type {
-            SignpostType::Event => sys::SIGNPOST_TYPE_EVENT,
-            SignpostType::IntervalBegin => sys::SIGNPOST_TYPE_INTERVAL_BEGIN,
-            SignpostType::IntervalEnd => sys::SIGNPOST_TYPE_INTERVAL_END,
-        };
+    fn new(refresh: Duration) -> Self {
+        Self {
+            refresh,
+            enabled: AtomicBool::new(false),
+            refreshed_at: AtomicU64::new(Self::NEVER),
+        }
+    }
 
-        // Dart SDK for reference on how to set up the format buffer:
-        // https://github.com/dart-lang/sdk/blob/3e2d3bc77fa8bb5139b869e9b3a5357b5487df18/runtime/vm/timeline_macos.cc#L34C1-L34C34
-        const FORMAT_BUFFER_LEN: usize = 64;
+    fn is_stale(&self) -> bool {
+        let refreshed_at = self.refreshed_at.load(Ordering::Relaxed);
+        refreshed_at == Self::NEVER
+            || monotonic_nanos().saturating_sub(refreshed_at) >= self.refresh.as_nanos() as u64
+    }
 
-        #[repr(align(16))]
-        struct AlignedBuffer {
-            data: [u8; FORMAT_BUFFER_LEN],
-        }
+    fn store(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        self.refreshed_at
+            .store(monotonic_nanos(), Ordering::Relaxed);
+    }
+}
 
-        static FORMAT_BUFFER: AlignedBuffer = AlignedBuffer {
-            data: [0; FORMAT_BUFFER_LEN],
-        };
+/// Monotonic clock in nanoseconds, relative to the first call.
+#[cfg(feature = "std")]
+fn monotonic_nanos() -> u64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
 
-        unsafe {
-            sys::_os_signpost_emit_with_name_impl(
-                (&raw mut sys::__dso_handle) as *mut usize as *mut c_void,
-                self.get(),
-                os_signpost_type,
-                id.0,
-                name_cstr.as_ptr(),
-                message_cstr
-                    .as_ref()
-                    .map(|msg| msg.as_ptr())
-                    .unwrap_or(std::ptr::null()),
-                &FORMAT_BUFFER.data as *const _ as *mut u8,
-                FORMAT_BUFFER_LEN as u32,
-            );
+#[cfg(feature = "std")]
+impl OsLog {
+    /// Create a new logger for the given subsystem and category
+    pub fn new(subsystem: String, category: &'static CStr) -> Self {
+        Self {
+            inner: Arc::new(LogHandle {
+                subsystem,
+                category,
+                handle: AtomicPtr::new(std::ptr::null_mut()),
+                init: std::sync::Once::new(),
+                #[cfg(all(feature = "debug_checks", debug_assertions))]
+                in_flight: Mutex::default(),
+            }),
+            scope: SignpostScope::default(),
+            enabled_cache: None,
+            sink: None,
+            max_name_len: DEFAULT_MAX_NAME_LEN,
+            dso_handle: None,
+            slow_threshold: None,
         }
     }
 
-    fn get(&self) -> sys::os_log_t {
-        self.init.call_once(|| {
-            let subsystem_cstr = std::ffi::CString::new(self.subsystem.as_str()).unwrap();
-            let handle =
-                unsafe { sys::os_log_create(subsystem_cstr.as_ptr(), self.category.as_ptr()) };
-            self.handle.store(handle, Ordering::SeqCst);
+    /// Wrap an `os_log_t` created outside of this crate, e.g. by another os_log crate.
+    ///
+    /// The logger has an empty subsystem and category, since they can't be read back from
+    /// the handle. [`OsLog::recording`] thus falls back to [`OsLog::enabled`].
+    ///
+    /// # Safety
+    /// `handle` must be a valid `os_log_t` for as long as the logger and its clones are
+    /// used. The handle isn't released when the logger is dropped, which is how handles
+    /// created by `os_log_create` are meant to be used.
+    pub unsafe fn from_raw_handle(handle: *mut c_void) -> Self {
+        let log = Self::new(String::new(), c"");
+        log.inner.init.call_once(|| {
+            log.inner
+                .handle
+                .store(handle.cast::<sys::os_log_s>(), Ordering::SeqCst);
         });
+        log
+    }
 
-        self.handle.load(Ordering::SeqCst)
+    /// The underlying `os_log_t`, created on first use.
+    ///
+    /// Share it with other os_log based crates so their messages line up with the
+    /// signposts in Console and Instruments. The handle stays valid for as long as this
+    /// logger or one of its clones is alive, and must not be released.
+    pub fn as_raw_handle(&self) -> *mut c_void {
+        self.get().cast()
     }
-}
 
-/// A signpost interval that represents a period of time being measured.
-///
-/// # Automatic Cleanup
-/// The interval will automatically emit an end signpost when it goes out of scope,
-/// due to its `Drop` implementation.
-pub struct SignpostInterval<'a> {
-    log: &'a OsLog,
-    id: SignpostId,
-    name: String,
-    message: Option<String>,
-}
+    /// Create a logger for the [`categories::POINTS_OF_INTEREST`] category.
+    pub fn points_of_interest<T: Into<String>>(subsystem: T) -> Self {
+        Self::new(subsystem.into(), categories::POINTS_OF_INTEREST)
+    }
 
-impl<'a> SignpostInterval<'a> {
-    fn new(log: &'a OsLog, id: SignpostId, name: &str, message: Option<&str>) -> Self {
-        let interval = Self {
-            log,
-            id,
-            name: name.to_string(),
-            message: message.map(|m| m.to_string()),
-        };
+    /// Create a logger for the [`categories::DYNAMIC_TRACING`] category.
+    pub fn dynamic_tracing<T: Into<String>>(subsystem: T) -> Self {
+        Self::new(subsystem.into(), categories::DYNAMIC_TRACING)
+    }
+
+    /// Create a logger for the [`categories::DYNAMIC_STACK_TRACING`] category.
+    pub fn dynamic_stack_tracing<T: Into<String>>(subsystem: T) -> Self {
+        Self::new(subsystem.into(), categories::DYNAMIC_STACK_TRACING)
+    }
 
-        if log.enabled() {
-            interval.start_interval();
+    /// Create a builder that validates the logger configuration up front.
+    pub fn builder<T: Into<String>>(subsystem: T) -> OsLogBuilder {
+        OsLogBuilder {
+            subsystem: subsystem.into(),
+            category: categories::POINTS_OF_INTEREST,
+            scope: SignpostScope::default(),
+            enabled_cache: None,
+            max_name_len: DEFAULT_MAX_NAME_LEN,
         }
+    }
 
-        interval
+    /// The subsystem of this logger.
+    pub fn subsystem(&self) -> &str {
+        &self.inner.subsystem
     }
 
-    fn start_interval(&self) {
-        self.log.emit(
-            self.id,
-            &self.name,
-            self.message.as_ref().map(|m| m.as_ref()),
-            SignpostType::IntervalBegin,
-        );
+    /// The category of this logger.
+    pub fn category(&self) -> &'static CStr {
+        self.inner.category
     }
 
-    fn end_internal(&self) {
-        self.log
-            // Don't repeat the start message as an end message.
-            .emit(self.id, &self.name, None, SignpostType::IntervalEnd);
+    /// Set the matching scope for intervals emitted through this logger.
+    ///
+    /// The os_signpost API has no per-emission scope argument: the system reads the
+    /// matching scope from the subsystem's logging configuration. The scope stored here
+    /// must agree with that configuration, and is used to validate signpost IDs, e.g.
+    /// pointer derived IDs are rejected for system-wide matching.
+    pub fn with_scope(mut self, scope: SignpostScope) -> Self {
+        self.scope = scope;
+        self
     }
-}
 
-impl Drop for SignpostInterval<'_> {
-    fn drop(&mut self) {
-        self.end_internal();
+    /// The matching scope of this logger.
+    pub fn scope(&self) -> SignpostScope {
+        self.scope
     }
-}
 
-static GLOBAL_CONFIG: OnceLock<(String, &'static CStr)> = OnceLock::new();
+    /// Cache the enabled state, refreshing it at most once per `refresh` interval.
+    ///
+    /// By default every call to [`OsLog::enabled`], and thus every emission, queries the
+    /// system. In hot paths the cached state avoids that FFI call at the cost of reacting
+    /// to Instruments starting or stopping a recording with a delay of up to `refresh`.
+    pub fn with_enabled_cache(mut self, refresh: Duration) -> Self {
+        self.enabled_cache = Some(Arc::new(EnabledCache::new(refresh)));
+        self
+    }
 
-/// Configuration builder for signpost tracer.
-pub struct Signpost {
-    subsystem: String,
-    category: &'static CStr,
-}
+    /// Emit signposts to `sink` instead of os_signpost.
+    ///
+    /// The sink also decides whether the logger is enabled. Meant for tests asserting which
+    /// signposts are emitted, see `MemorySink` with the `test-util` feature.
+    pub fn with_sink(mut self, sink: Arc<dyn SignpostSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
 
-impl Signpost {
-    /// Initializes the process global signpost configuration.
-    pub fn configure(subsystem: &str, category: &'static CStr) -> Self {
-        let config = Self {
-            subsystem: subsystem.to_string(),
-            category,
-        };
+    /// Truncate signpost names longer than `max_len` bytes, [`DEFAULT_MAX_NAME_LEN`] by default.
+    ///
+    /// Instruments doesn't display arbitrarily long names, which the `module::function::name`
+    /// names of the macros can get. Truncated names end with an ellipsis, cut on a character
+    /// boundary so they stay valid UTF-8. Both ends of an interval are truncated alike, so
+    /// they still match.
+    pub fn with_max_name_len(mut self, max_len: usize) -> Self {
+        self.max_name_len = max_len;
+        self
+    }
 
-        GLOBAL_CONFIG
-            .set((config.subsystem.clone(), config.category))
-            .expect("Signpost already configured");
+    /// The maximum length of signpost names in bytes.
+    pub fn max_name_len(&self) -> usize {
+        self.max_name_len
+    }
 
-        config
+    /// Emit an event when an interval lasts longer than `threshold`, to spot slow operations.
+    ///
+    /// The event has the name and ID of the interval and the message `slow`, and is emitted
+    /// right before the end signpost, so it shows up at the end of the interval and the
+    /// outliers can be found by filtering for the message in Instruments. Only intervals
+    /// whose begin signpost was emitted are measured, so nothing is compared while the
+    /// logger is disabled.
+    pub fn with_slow_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_threshold = Some(threshold);
+        self
     }
-}
 
-/// Get the global logger for signpost operations.
-#[doc(hidden)]
-pub fn global_logger() -> &'static OsLog {
-    // Use a static OnceLock for the actual logger instance
-    static GLOBAL_LOGGER: OnceLock<OsLog> = OnceLock::new();
+    /// The duration after which intervals are slow, see [`OsLog::with_slow_threshold`].
+    pub fn slow_threshold(&self) -> Option<Duration> {
+        self.slow_threshold
+    }
+
+    /// Attribute signposts to the image with the DSO handle `handle`, or to the image
+    /// containing this crate again if `handle` is null.
+    ///
+    /// os_signpost records the image each signpost is emitted from. The crate passes the
+    /// `__dso_handle` of the image it's linked into, which is the right one when it is
+    /// statically linked into the instrumented code, including `cdylib` plugins loaded by a
+    /// host. Only when the crate is in another image than the code using it, e.g. as a Rust
+    /// `dylib` shared by several plugins, would signposts be attributed to that image. Pass
+    /// the handle of the calling image then, e.g. `dli_fbase` from `dladdr`.
+    ///
+    /// # Safety
+    /// `handle` must be the Mach-O header of an image that stays loaded for as long as the
+    /// logger and its clones emit signposts.
+    pub unsafe fn with_dso_handle(mut self, handle: *const c_void) -> Self {
+        self.dso_handle = NonZeroUsize::new(handle as usize);
+        self
+    }
+
+    /// The DSO handle of the image signposts are attributed to, see
+    /// [`OsLog::with_dso_handle`].
+    pub fn dso_handle(&self) -> *const c_void {
+        match self.dso_handle {
+            Some(handle) => handle.get() as *const c_void,
+            None => (&raw const sys::__dso_handle).cast(),
+        }
+    }
+
+    /// The sink signposts are emitted to.
+    fn sink(&self) -> &dyn SignpostSink {
+        #[cfg(feature = "console")]
+        let default = &ConsoleSink;
+        #[cfg(not(feature = "console"))]
+        let default = &OsSink;
+        self.sink.as_deref().unwrap_or(default)
+    }
+
+    /// Check if signpost logging is enabled for this logger
+    ///
+    /// The enabled state depends on the category: [`categories::POINTS_OF_INTEREST`] is
+    /// enabled whenever any consumer is interested, while the dynamic tracing categories are
+    /// only enabled while a performance tool is recording. See [`OsLog::recording`].
+    ///
+    /// Always false while emission is turned off with [`Signpost::set_enabled`].
+    pub fn enabled(&self) -> bool {
+        if !EMISSION_ENABLED.load(Ordering::Relaxed) {
+            return false;
+        }
+        match &self.enabled_cache {
+            Some(cache) if !cache.is_stale() => cache.enabled.load(Ordering::Relaxed),
+            _ => self.refresh_enabled(),
+        }
+    }
+
+    /// Query the system for the enabled state, updating the cache if one is configured.
+    pub fn refresh_enabled(&self) -> bool {
+        let enabled = !cfg!(feature = "disabled") && self.sink().enabled(self);
+        if let Some(cache) = &self.enabled_cache {
+            cache.store(enabled);
+        }
+        enabled
+    }
+
+    /// Check if a performance tool like Instruments is actively recording this subsystem
+    ///
+    /// The os_signpost API has no direct query for this. Instead the dynamic tracing
+    /// categories are only enabled while a recording is in progress, so this checks
+    /// [`categories::DYNAMIC_TRACING`] of the same subsystem. Use it to only build expensive
+    /// messages when a full trace is captured, even on a points of interest logger.
+    pub fn recording(&self) -> bool {
+        let category = self.category();
+        // Wrapped raw handles have no known subsystem.
+        if self.subsystem().is_empty()
+            || category == categories::DYNAMIC_TRACING
+            || category == categories::DYNAMIC_STACK_TRACING
+        {
+            return self.enabled();
+        }
+        self.with_category(categories::DYNAMIC_TRACING).enabled()
+    }
+
+    /// The [`categories::DYNAMIC_STACK_TRACING`] logger of the same subsystem.
+    ///
+    /// The logger keeps the sink and the other settings of this one, only the category
+    /// differs. Returns a clone of this logger if it already uses that category.
+    pub fn stack_tracing_logger(&self) -> OsLog {
+        if self.category() == categories::DYNAMIC_STACK_TRACING || self.subsystem().is_empty() {
+            return self.clone();
+        }
+        self.with_category(categories::DYNAMIC_STACK_TRACING)
+    }
+
+    /// A clone of this logger using `category` instead.
+    ///
+    /// Shares the os_log handle with the cached logger of the subsystem and category.
+    fn with_category(&self, category: &'static CStr) -> OsLog {
+        let shared = cached_logger(self.subsystem(), category);
+        OsLog {
+            inner: shared.inner.clone(),
+            // The enabled state differs between categories, so don't share the cache.
+            enabled_cache: self
+                .enabled_cache
+                .as_ref()
+                .map(|cache| Arc::new(EnabledCache::new(cache.refresh))),
+            ..self.clone()
+        }
+    }
+
+    /// Emit an event that Instruments annotates with the user backtrace at the call site
+    ///
+    /// Backtraces are only captured for signposts of the
+    /// [`categories::DYNAMIC_STACK_TRACING`] category, so the event is emitted on
+    /// [`OsLog::stack_tracing_logger`]. Like the dynamic tracing category, it is only enabled
+    /// while a performance tool records the subsystem.
+    ///
+    /// Capturing a backtrace is considerably more expensive than a plain event, so prefer
+    /// it for rare events whose origin is otherwise unclear over hot paths.
+    pub fn event_with_backtrace<T: AsRef<str>>(&self, id: SignpostId, name: T) {
+        self.stack_tracing_logger().event(id, name);
+    }
+
+    /// Emit a simple event (point in time)
+    pub fn event<T: AsRef<str>>(&self, id: SignpostId, name: T) {
+        self.emit(id, name.as_ref(), None, SignpostKind::Event);
+    }
+
+    /// Emit a simple event with a static C string name
+    ///
+    /// Unlike [`OsLog::event`], the name is passed to os_signpost as is, without copying it
+    /// into a `CString` for every emission. Use it for high-frequency events with literal
+    /// names, e.g. `log.event_cstr(id, c"checkpoint")`. The macros can't, since they prefix
+    /// the name with the calling function.
+    pub fn event_cstr(&self, id: SignpostId, name: &'static CStr) {
+        self.emit_raw(id, name, None, SignpostKind::Event);
+    }
+
+    /// Emit an event only when `cond` is true
+    pub fn event_if<T: AsRef<str>>(&self, cond: bool, id: SignpostId, name: T) {
+        if cond {
+            self.event(id, name);
+        }
+    }
+
+    /// Emit an event with a formatted message
+    pub fn event_with_message<T1: AsRef<str>, T2: AsRef<str>>(
+        &self,
+        id: SignpostId,
+        name: T1,
+        message: T2,
+    ) {
+        self.event_with_args(
+            id,
+            name,
+            MESSAGE_FORMAT,
+            &[SignpostArg::Str(message.as_ref())],
+        );
+    }
+
+    /// Emit an event with a message built from `format_args!`
+    ///
+    /// The message is only formatted when signposts are enabled, so disabled events don't
+    /// pay for formatting or allocation.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// log.event_fmt(id, "Request", format_args!("{method} {path}"));
+    /// ```
+    pub fn event_fmt<T: AsRef<str>>(&self, id: SignpostId, name: T, args: fmt::Arguments<'_>) {
+        if !self.enabled() {
+            return;
+        }
+
+        match args.as_str() {
+            Some(message) => self.event_with_message(id, name, message),
+            None => self.event_with_message(id, name, args.to_string()),
+        }
+    }
+
+    /// Emit an event with `key=value` fields as the message
+    ///
+    /// The fields are only formatted when signposts are enabled. See
+    /// [`OsLog::interval_with_fields`] for how fields show up in Instruments.
+    pub fn event_with_fields<T: AsRef<str>>(
+        &self,
+        id: SignpostId,
+        name: T,
+        fields: &[(&str, SignpostArg<'_>)],
+    ) {
+        if self.enabled() {
+            self.event_with_message(id, name, format_fields(fields));
+        }
+    }
+
+    /// Emit an event with a format string and typed arguments
+    ///
+    /// The arguments are marshalled into the os_log format buffer, so numeric values keep
+    /// their type and can be graphed in Instruments. Each argument must match a conversion
+    /// specifier in `format`, e.g. `c"%{public}lld bytes"` for a single `SignpostArg::I64`.
+    pub fn event_with_args<T: AsRef<str>>(
+        &self,
+        id: SignpostId,
+        name: T,
+        format: &CStr,
+        args: &[SignpostArg<'_>],
+    ) {
+        self.emit_with_args(id, name.as_ref(), Some(format), args, SignpostKind::Event);
+    }
+
+    /// Emit an event carrying a numeric measurement, e.g. a queue depth or a latency
+    ///
+    /// The value is passed as a typed `%{public}f` argument instead of text, so Instruments
+    /// can plot it over time, e.g. with a custom instrument graphing the events of `name`.
+    pub fn measure<T: AsRef<str>>(&self, id: SignpostId, name: T, value: f64) {
+        self.event_with_args(id, name, MEASURE_FORMAT, &[SignpostArg::F64(value)]);
+    }
+
+    /// Start a signpost interval
+    pub fn interval<T: AsRef<str>>(&self, id: SignpostId, name: T) -> SignpostInterval<'_> {
+        SignpostInterval::new(IntervalLog::Borrowed(self), id, name.as_ref(), None)
+    }
+
+    /// Start a signpost interval only when `cond` is true
+    ///
+    /// Unlike starting the interval inside an `if` block, the returned guard can be bound
+    /// for the rest of the enclosing scope:
+    ///
+    /// ```ignore
+    /// let _interval = log.interval_if(verbose, id, "Parse");
+    /// ```
+    pub fn interval_if<T: AsRef<str>>(
+        &self,
+        cond: bool,
+        id: SignpostId,
+        name: T,
+    ) -> Option<SignpostInterval<'_>> {
+        cond.then(|| self.interval(id, name))
+    }
+
+    /// Start a signpost interval, also emitting an event of the same name at its start
+    ///
+    /// The event shows up in the events lane as well, which helps correlating the start of
+    /// a long interval with other point events on the same timeline.
+    pub fn interval_with_begin_event<T: AsRef<str>>(
+        &self,
+        id: SignpostId,
+        name: T,
+    ) -> SignpostInterval<'_> {
+        let name = name.as_ref();
+        self.event(id, name);
+        self.interval(id, name)
+    }
+
+    /// Start a signpost interval with a message
+    pub fn interval_with_message<T1: AsRef<str>, T2: AsRef<str>>(
+        &self,
+        id: SignpostId,
+        name: T1,
+        message: T2,
+    ) -> SignpostInterval<'_> {
+        SignpostInterval::new(
+            IntervalLog::Borrowed(self),
+            id,
+            name.as_ref(),
+            Some(message.as_ref()),
+        )
+    }
+
+    /// Start a signpost interval whose name and message are only built if it begins.
+    ///
+    /// Used by [`interval_with_message!`] to skip formatting while signposts are disabled.
+    #[doc(hidden)]
+    pub fn interval_lazy<F, M>(&self, id: SignpostId, describe: F) -> SignpostInterval<'_>
+    where
+        F: FnOnce() -> (String, M),
+        M: AsRef<str>,
+    {
+        SignpostInterval::new_lazy(IntervalLog::Borrowed(self), id, || {
+            let (name, message) = describe();
+            (name, Some(message.as_ref().to_string()))
+        })
+    }
+
+    /// Start a signpost interval with `key=value` fields as the message
+    ///
+    /// The fields are rendered as e.g. `size=1024 status=ok`, which custom Instruments
+    /// packages can parse into sortable columns. They are only formatted when signposts
+    /// are enabled.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let _interval = log.interval_with_fields(
+    ///     id,
+    ///     "Upload",
+    ///     &[("size", SignpostArg::U64(1024)), ("status", SignpostArg::Str("ok"))],
+    /// );
+    /// ```
+    pub fn interval_with_fields<T: AsRef<str>>(
+        &self,
+        id: SignpostId,
+        name: T,
+        fields: &[(&str, SignpostArg<'_>)],
+    ) -> SignpostInterval<'_> {
+        let message = self.enabled().then(|| format_fields(fields));
+        SignpostInterval::new(
+            IntervalLog::Borrowed(self),
+            id,
+            name.as_ref(),
+            message.as_deref(),
+        )
+    }
+
+    /// Start a signpost interval that records the ID of a parent interval in its message
+    ///
+    /// Instruments only nests intervals by time, so intervals of the same subsystem that
+    /// overlap without being lexically nested can't be told apart. The message is
+    /// `parent=<hex id>`, from which post-processing can reconstruct the tree.
+    pub fn interval_with_parent<T: AsRef<str>>(
+        &self,
+        id: SignpostId,
+        name: T,
+        parent: SignpostId,
+    ) -> SignpostInterval<'_> {
+        let message = self.enabled().then(|| format!("parent={parent}"));
+        SignpostInterval::new(
+            IntervalLog::Borrowed(self),
+            id,
+            name.as_ref(),
+            message.as_deref(),
+        )
+    }
+
+    /// Create a builder for an interval, to combine an ID, message and fields.
+    ///
+    /// For the common case without options, use [`OsLog::interval`].
+    pub fn interval_builder<T: Into<String>>(&self, name: T) -> IntervalBuilder<'_> {
+        IntervalBuilder {
+            log: self,
+            name: name.into(),
+            id: None,
+            message: SignpostMessage::new(self),
+        }
+    }
+
+    /// Create a builder for an event, see [`OsLog::interval_builder`].
+    pub fn event_builder<T: Into<String>>(&self, name: T) -> EventBuilder<'_> {
+        EventBuilder {
+            log: self,
+            name: name.into(),
+            id: None,
+            message: SignpostMessage::new(self),
+        }
+    }
+
+    /// Create a group of intervals that all end when the group is dropped.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let _resources = log
+    ///     .group()
+    ///     .interval(SignpostId::next(), "Database")
+    ///     .interval(SignpostId::next(), "Cache");
+    /// // Both intervals end here, "Cache" first.
+    /// ```
+    pub fn group(&self) -> IntervalGroup<'_> {
+        IntervalGroup {
+            log: self,
+            intervals: Vec::new(),
+        }
+    }
+
+    /// Start a signpost interval that keeps a reference to the log instead of borrowing it
+    ///
+    /// The returned interval has no lifetime parameter, so it can be stored in a struct.
+    pub fn interval_owned<T: AsRef<str>>(
+        self: &Arc<Self>,
+        id: SignpostId,
+        name: T,
+    ) -> OwnedSignpostInterval {
+        SignpostInterval::new(
+            IntervalLog::Shared(Arc::clone(self)),
+            id,
+            name.as_ref(),
+            None,
+        )
+    }
+
+    /// Start a signpost interval with a message that keeps a reference to the log, see
+    /// [`OsLog::interval_owned`].
+    pub fn interval_owned_with_message<T1: AsRef<str>, T2: AsRef<str>>(
+        self: &Arc<Self>,
+        id: SignpostId,
+        name: T1,
+        message: T2,
+    ) -> OwnedSignpostInterval {
+        SignpostInterval::new(
+            IntervalLog::Shared(Arc::clone(self)),
+            id,
+            name.as_ref(),
+            Some(message.as_ref()),
+        )
+    }
+
+    /// Emit an interval begin signpost without a guard ending it.
+    ///
+    /// The interval must be ended with [`OsLog::end_interval_raw`] using the same `id` and
+    /// `name`. This allows ending an interval in another process than the one it began in:
+    /// serialize the `SignpostId` and hand it to the other process. Both processes must log
+    /// to the same subsystem and category, and the subsystem must be configured for
+    /// system-wide matching, see [`SignpostScope::System`].
+    pub fn begin_interval_raw<T: AsRef<str>>(&self, id: SignpostId, name: T) {
+        self.emit(id, name.as_ref(), None, SignpostKind::IntervalBegin);
+    }
+
+    /// Emit an interval end signpost for an interval begun with [`OsLog::begin_interval_raw`].
+    pub fn end_interval_raw<T: AsRef<str>>(&self, id: SignpostId, name: T) {
+        self.emit(id, name.as_ref(), None, SignpostKind::IntervalEnd);
+    }
+
+    /// Mark a checkpoint, e.g. before a snapshot, crash dump or shutdown.
+    ///
+    /// os_signpost has no flush or barrier: every signpost is written to the log buffers of
+    /// the system before its emitting call returns, in the order of the calls on a thread,
+    /// and interleaved with os_log messages of that thread in call order. Signposts emitted
+    /// before `flush` returns are thus already committed, also when the process crashes or
+    /// panics right after.
+    ///
+    /// This emits a [`FLUSH_EVENT`] event, so the checkpoint shows up in Instruments, and
+    /// flushes a custom sink set with [`OsLog::with_sink`] that buffers signposts.
+    pub fn flush(&self) {
+        if cfg!(feature = "disabled") {
+            return;
+        }
+        self.event(SignpostId::next(), FLUSH_EVENT);
+        self.sink().flush(self);
+    }
+
+    /// Emit a signpost from C strings, without the generic wrappers
+    ///
+    /// This is a single flat entry point for callers binding the crate from other languages,
+    /// which already hold C strings: neither the name nor the message are copied.
+    pub fn emit_raw(
+        &self,
+        id: SignpostId,
+        name: &CStr,
+        message: Option<&CStr>,
+        kind: SignpostKind,
+    ) {
+        if cfg!(feature = "disabled") || !self.enabled() {
+            return;
+        }
+        let truncated;
+        let name = if name.to_bytes().len() > self.max_name_len {
+            truncated = to_cstring(&truncate_name(&name.to_string_lossy(), self.max_name_len));
+            truncated.as_c_str()
+        } else {
+            name
+        };
+        self.check_in_flight(id, || name.to_string_lossy().into_owned(), kind);
+
+        if self.sink.is_some() || cfg!(feature = "console") {
+            let sink = self.sink();
+            let message = message.map(CStr::to_string_lossy);
+            let args = message.as_deref().map(SignpostArg::Str);
+            let emission = Emission {
+                id,
+                name: &name.to_string_lossy(),
+                format: message.is_some().then_some(MESSAGE_FORMAT),
+                args: args.as_slice(),
+                kind,
+            };
+            sink.emit(self, &emission);
+            return;
+        }
+
+        match message {
+            Some(message) => self.emit_encoded(
+                id,
+                name,
+                Some(MESSAGE_FORMAT),
+                FormatBuffer::encode_message(message),
+                kind,
+            ),
+            None => self.emit_encoded(id, name, None, FormatBuffer::encode(None, &[]), kind),
+        }
+    }
+
+    /// Emit a signpost with an optional plain message
+    pub(crate) fn emit(
+        &self,
+        id: SignpostId,
+        name: &str,
+        message: Option<&str>,
+        signpost_type: SignpostKind,
+    ) {
+        match message {
+            Some(message) => self.emit_with_args(
+                id,
+                name,
+                Some(MESSAGE_FORMAT),
+                &[SignpostArg::Str(message)],
+                signpost_type,
+            ),
+            None => self.emit_with_args(id, name, None, &[], signpost_type),
+        }
+    }
+
+    /// Centralized signpost emission function
+    pub(crate) fn emit_with_args(
+        &self,
+        id: SignpostId,
+        name: &str,
+        format: Option<&CStr>,
+        args: &[SignpostArg<'_>],
+        signpost_type: SignpostKind,
+    ) {
+        // Checked separately so the emission is compiled out with the `disabled` feature.
+        if cfg!(feature = "disabled") || !self.enabled() {
+            return;
+        }
+
+        // Sinks see the name like os_signpost shows it, see `to_cstring`.
+        let name = if name.contains('\0') {
+            std::borrow::Cow::Owned(name.replace('\0', "\u{FFFD}"))
+        } else {
+            std::borrow::Cow::Borrowed(name)
+        };
+        let name = truncate_name(&name, self.max_name_len);
+        self.check_in_flight(id, || name.to_string(), signpost_type);
+
+        let emission = Emission {
+            id,
+            name: &name,
+            format,
+            args,
+            kind: signpost_type,
+        };
+        self.sink().emit(self, &emission);
+    }
+
+    /// Panics when an interval begins while one with the same ID and name is in flight,
+    /// which would corrupt the begin and end matching in Instruments.
+    ///
+    /// Only with the `debug_checks` feature in debug builds, a no-op otherwise.
+    #[inline(always)]
+    #[cfg_attr(
+        not(all(feature = "debug_checks", debug_assertions)),
+        allow(unused_variables)
+    )]
+    fn check_in_flight(&self, id: SignpostId, name: impl FnOnce() -> String, kind: SignpostKind) {
+        #[cfg(all(feature = "debug_checks", debug_assertions))]
+        {
+            let mut in_flight = self
+                .inner
+                .in_flight
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            match kind {
+                SignpostKind::IntervalBegin => {
+                    let name = name();
+                    assert!(
+                        !in_flight.contains(&(id, name.clone())),
+                        "signpost interval `{name}` with ID {id} began while an interval with \
+                         the same name and ID is in flight"
+                    );
+                    in_flight.insert((id, name));
+                }
+                SignpostKind::IntervalEnd => {
+                    in_flight.remove(&(id, name()));
+                }
+                SignpostKind::Event => {}
+            }
+        }
+    }
+
+    /// Emits a signpost with an encoded format buffer, once enabled has been checked.
+    pub(crate) fn emit_encoded(
+        &self,
+        id: SignpostId,
+        name: &CStr,
+        format: Option<&CStr>,
+        mut buffer: FormatBuffer,
+        signpost_type: SignpostKind,
+    ) {
+        let os_signpost_type: sys::os_signpost_type_t = signpost_type.into();
+
+        unsafe {
+            sys::_os_signpost_emit_with_name_impl(
+                self.dso_handle().cast_mut(),
+                self.get(),
+                os_signpost_type,
+                id.0,
+                name.as_ptr(),
+                format.map(CStr::as_ptr).unwrap_or(std::ptr::null()),
+                buffer.as_mut_ptr(),
+                buffer.len() as u32,
+            );
+        }
+    }
+
+    pub(crate) fn get(&self) -> sys::os_log_t {
+        let inner = &*self.inner;
+        inner.init.call_once(|| {
+            let subsystem_cstr = CString::new(inner.subsystem.as_str()).unwrap();
+            let handle =
+                unsafe { sys::os_log_create(subsystem_cstr.as_ptr(), inner.category.as_ptr()) };
+            inner.handle.store(handle, Ordering::SeqCst);
+        });
+
+        inner.handle.load(Ordering::SeqCst)
+    }
+}
+
+/// End message of intervals dropped while unwinding from a panic.
+#[cfg(feature = "std")]
+const PANIC_MESSAGE: &str = "panicked";
+
+/// End message of intervals of futures dropped before completing.
+#[cfg(feature = "std")]
+const CANCEL_MESSAGE: &str = "cancelled";
+
+/// Message of the events marking intervals that exceed the slow threshold.
+#[cfg(feature = "std")]
+const SLOW_MESSAGE: &str = "slow";
+
+/// The log an interval emits to, either borrowed or shared.
+#[cfg(feature = "std")]
+enum IntervalLog<'a> {
+    Borrowed(&'a OsLog),
+    Shared(Arc<OsLog>),
+}
+
+#[cfg(feature = "std")]
+impl Deref for IntervalLog<'_> {
+    type Target = OsLog;
+
+    fn deref(&self) -> &OsLog {
+        match self {
+            IntervalLog::Borrowed(log) => log,
+            IntervalLog::Shared(log) => log,
+        }
+    }
+}
+
+/// A signpost interval that represents a period of time being measured.
+///
+/// # Automatic Cleanup
+/// The interval will automatically emit an end signpost when it goes out of scope,
+/// due to its `Drop` implementation. Use [`SignpostInterval::end`] to end it earlier.
+/// Intervals dropped while unwinding from a panic end with a "panicked" message.
+#[cfg(feature = "std")]
+pub struct SignpostInterval<'a> {
+    log: IntervalLog<'a>,
+    id: SignpostId,
+    name: String,
+    message: Option<String>,
+    /// Start time, only captured when the begin signpost was emitted.
+    started_at: Option<Instant>,
+    ended: bool,
+    /// Entry naming nested intervals after this one, see [`Signpost::set_breadcrumbs`].
+    breadcrumb: Option<breadcrumbs::Breadcrumb>,
+    /// Nesting depth, see [`Signpost::set_max_interval_depth`].
+    depth: Option<depth::Depth>,
+    /// Whether dropping the interval means its future was cancelled.
+    cancellable: bool,
+    /// Message of the end signpost, see [`SignpostInterval::set_end_message`].
+    end_message: Option<String>,
+    /// Appended to the end message, see [`SignpostInterval::annotate`].
+    annotation: Option<String>,
+}
+
+/// A signpost interval that owns its log, so it can be stored without borrowing.
+///
+/// Created with [`OsLog::interval_owned`], or by the macros from the `'static` global logger.
+/// Useful for intervals that last as long as the struct holding them, e.g. a connection.
+#[cfg(feature = "std")]
+pub type OwnedSignpostInterval = SignpostInterval<'static>;
+
+#[cfg(feature = "std")]
+impl<'a> SignpostInterval<'a> {
+    fn new(log: IntervalLog<'a>, id: SignpostId, name: &str, message: Option<&str>) -> Self {
+        let mut interval = Self::unstarted(log, id);
+        interval.name = name.to_string();
+        interval.message = message.map(str::to_string);
+        if interval.can_begin() {
+            interval.begin();
+        }
+        interval
+    }
+
+    /// Like [`SignpostInterval::new`], only building the name and message if the interval
+    /// begins.
+    fn new_lazy(
+        log: IntervalLog<'a>,
+        id: SignpostId,
+        describe: impl FnOnce() -> (String, Option<String>),
+    ) -> Self {
+        let mut interval = Self::unstarted(log, id);
+        if interval.can_begin() {
+            (interval.name, interval.message) = describe();
+            interval.begin();
+        }
+        interval
+    }
+
+    /// An interval without name and message that hasn't begun, counted in the depth.
+    fn unstarted(log: IntervalLog<'a>, id: SignpostId) -> Self {
+        Self {
+            log,
+            id,
+            name: String::new(),
+            message: None,
+            started_at: None,
+            ended: false,
+            breadcrumb: None,
+            depth: depth::Depth::enter(),
+            cancellable: false,
+            end_message: None,
+            annotation: None,
+        }
+    }
+
+    /// Whether the interval is emitted: not nested too deep and its log is enabled.
+    fn can_begin(&self) -> bool {
+        let suppressed = self.depth.as_ref().is_some_and(depth::Depth::suppressed);
+        !suppressed && self.log.enabled()
+    }
+
+    /// Emit the begin signpost.
+    fn begin(&mut self) {
+        if BREADCRUMBS.load(Ordering::Relaxed) {
+            self.name = breadcrumbs::compose(&self.name);
+            self.breadcrumb = Some(breadcrumbs::push(self.name.clone()));
+        }
+        self.started_at = Some(Instant::now());
+        self.start_interval();
+    }
+
+    fn start_interval(&self) {
+        self.log.emit(
+            self.id,
+            &self.name,
+            self.message.as_ref().map(|m| m.as_ref()),
+            SignpostKind::IntervalBegin,
+        );
+    }
+
+    /// The signpost ID of the interval.
+    ///
+    /// Events emitted with this ID are shown as part of the interval in Instruments.
+    pub fn id(&self) -> SignpostId {
+        self.id
+    }
+
+    /// Emit an event within the interval, using the interval's log and ID
+    ///
+    /// Instruments shows events sharing the ID of an interval within that interval's lane.
+    pub fn event<T: AsRef<str>>(&self, name: T) {
+        self.log.event(self.id, name);
+    }
+
+    /// Emit an event with a message within the interval, see [`SignpostInterval::event`].
+    pub fn event_with_message<T1: AsRef<str>, T2: AsRef<str>>(&self, name: T1, message: T2) {
+        self.log.event_with_message(self.id, name, message);
+    }
+
+    /// Convert the interval into one that owns its log, so it can be moved into a spawned
+    /// thread or task.
+    ///
+    /// A borrowed log is cloned, which shares the underlying handle, so the interval still
+    /// ends on the log it began on.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let interval = log.interval(SignpostId::next(), "Detached").into_owned();
+    /// tokio::spawn(async move {
+    ///     work().await;
+    ///     drop(interval);
+    /// });
+    /// ```
+    pub fn into_owned(mut self) -> OwnedSignpostInterval {
+        let log = match &self.log {
+            IntervalLog::Borrowed(log) => IntervalLog::Shared(Arc::new((*log).clone())),
+            IntervalLog::Shared(log) => IntervalLog::Shared(Arc::clone(log)),
+        };
+        let owned = SignpostInterval {
+            log,
+            id: self.id,
+            name: std::mem::take(&mut self.name),
+            message: self.message.take(),
+            started_at: self.started_at,
+            ended: self.ended,
+            breadcrumb: self.breadcrumb.take(),
+            depth: self.depth.take(),
+            cancellable: self.cancellable,
+            end_message: self.end_message.take(),
+            annotation: self.annotation.take(),
+        };
+        // The owned interval ends it instead.
+        self.ended = true;
+        owned
+    }
+
+    /// Mark the interval of a future as cancelled if it is dropped before completing.
+    ///
+    /// Used by `#[signpost]` on futures, which clears the mark once the future completes.
+    #[doc(hidden)]
+    pub fn set_cancellable(&mut self, cancellable: bool) {
+        self.cancellable = cancellable;
+    }
+
+    /// Set the message of the end signpost, whether the interval ends explicitly or when
+    /// it is dropped.
+    ///
+    /// Use it to attach a result as a function proceeds, without consuming the interval.
+    /// Later calls replace the message, and [`SignpostInterval::end_with_message`] as well
+    /// as the messages of intervals dropped by a panic or a cancelled future take precedence.
+    /// The message is only stored while the interval is emitted.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let mut interval = log.interval(SignpostId::next(), "Lookup");
+    /// let entry = cache.get(key);
+    /// interval.set_end_message(if entry.is_some() { "hit" } else { "miss" });
+    /// ```
+    pub fn set_end_message<T: AsRef<str>>(&mut self, message: T) {
+        if self.started_at.is_some() {
+            self.end_message = Some(message.as_ref().to_string());
+        }
+    }
+
+    /// Add `extra` to the message of the end signpost, e.g. a label that is only known
+    /// after the interval began.
+    ///
+    /// The name of the interval can't change instead: Instruments matches the end signpost
+    /// to the begin signpost by their log, ID and name, so a renamed end would leave both
+    /// unmatched. Annotations follow the end message, separated by spaces, however the
+    /// interval ends. They are only stored while the interval is emitted.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let mut interval = log.interval(SignpostId::next(), "Request");
+    /// let request = parse(&buffer)?;
+    /// interval.annotate(&format!("method={}", request.method));
+    /// ```
+    pub fn annotate(&mut self, extra: &str) {
+        if self.started_at.is_none() {
+            return;
+        }
+        match &mut self.annotation {
+            Some(annotation) => {
+                annotation.push(' ');
+                annotation.push_str(extra);
+            }
+            None => self.annotation = Some(extra.to_string()),
+        }
+    }
+
+    /// Wall-clock time since the interval began.
+    ///
+    /// The start time is only captured when signposts are enabled, otherwise this
+    /// returns `Duration::ZERO`.
+    pub fn elapsed(&self) -> Duration {
+        self.started_at
+            .map(|started_at| started_at.elapsed())
+            .unwrap_or_default()
+    }
+
+    /// End the interval immediately instead of when it is dropped.
+    ///
+    /// The end signpost carries the message set with [`SignpostInterval::set_end_message`],
+    /// if any. Returns the duration of the interval, see [`SignpostInterval::elapsed`].
+    pub fn end(mut self) -> Duration {
+        let elapsed = self.elapsed();
+        // Don't repeat the start message as an end message.
+        self.end_internal(None);
+        elapsed
+    }
+
+    /// End the interval immediately, attaching a message to the end signpost.
+    ///
+    /// Returns the duration of the interval, see [`SignpostInterval::elapsed`].
+    pub fn end_with_message<T: AsRef<str>>(mut self, message: T) -> Duration {
+        let elapsed = self.elapsed();
+        self.end_internal(Some(message.as_ref()));
+        elapsed
+    }
+
+    /// Emits the end signpost unless the interval has already ended.
+    ///
+    /// Returns whether an end signpost was emitted.
+    fn end_internal(&mut self, message: Option<&str>) -> bool {
+        if self.ended {
+            return false;
+        }
+
+        self.ended = true;
+        if let Some(breadcrumb) = self.breadcrumb.take() {
+            breadcrumb.pop();
+        }
+        if let Some(depth) = self.depth.take() {
+            depth.exit();
+        }
+        // Without a begin signpost, e.g. when recording started mid-interval, the end
+        // signpost would be an orphan.
+        let Some(started_at) = self.started_at else {
+            return false;
+        };
+        if let Some(threshold) = self.log.slow_threshold {
+            if started_at.elapsed() > threshold {
+                self.log
+                    .emit(self.id, &self.name, Some(SLOW_MESSAGE), SignpostKind::Event);
+            }
+        }
+        let annotated;
+        let message = match (message.or(self.end_message.as_deref()), &self.annotation) {
+            (Some(message), Some(annotation)) => {
+                annotated = format!("{message} {annotation}");
+                Some(annotated.as_str())
+            }
+            (message, annotation) => message.or(annotation.as_deref()),
+        };
+        self.log
+            .emit(self.id, &self.name, message, SignpostKind::IntervalEnd);
+        true
+    }
+}
+
+#[cfg(feature = "std")]
+impl SignpostInterval<'static> {
+    /// An interval that never emits, used by the macros with the `disabled` feature or
+    /// while signposts are disabled.
+    #[doc(hidden)]
+    pub fn disabled() -> Self {
+        static LOG: OnceLock<OsLog> = OnceLock::new();
+        let log = LOG.get_or_init(|| {
+            OsLog::new(
+                UNCONFIGURED_SUBSYSTEM.to_string(),
+                categories::POINTS_OF_INTEREST,
+            )
+        });
+        Self {
+            log: IntervalLog::Borrowed(log),
+            id: SignpostId(1),
+            name: String::new(),
+            message: None,
+            started_at: None,
+            ended: true,
+            breadcrumb: None,
+            depth: None,
+            cancellable: false,
+            end_message: None,
+            annotation: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for SignpostInterval<'_> {
+    fn drop(&mut self) {
+        // Don't repeat the start message as an end message, but mark intervals that end
+        // because of a panic or a cancelled future. With `panic = "abort"` no end signpost
+        // is emitted.
+        let message = if std::thread::panicking() {
+            Some(PANIC_MESSAGE)
+        } else {
+            self.cancellable.then_some(CANCEL_MESSAGE)
+        };
+        self.end_internal(message);
+    }
+}
+
+/// Builder for a [`SignpostInterval`], created with [`OsLog::interval_builder`].
+///
+/// # Examples
+/// ```ignore
+/// let _interval = log
+///     .interval_builder("Upload")
+///     .message("retry")
+///     .field("size", SignpostArg::U64(1024))
+///     .start();
+/// ```
+#[cfg(feature = "std")]
+pub struct IntervalBuilder<'a> {
+    log: &'a OsLog,
+    name: String,
+    id: Option<SignpostId>,
+    message: SignpostMessage,
+}
+
+#[cfg(feature = "std")]
+impl<'a> IntervalBuilder<'a> {
+    /// Set the signpost ID, [`SignpostId::next`] by default.
+    pub fn id(mut self, id: SignpostId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Set the message of the begin signpost.
+    pub fn message<T: AsRef<str>>(mut self, message: T) -> Self {
+        self.message.set(message.as_ref());
+        self
+    }
+
+    /// Append a `key=value` field to the message, see [`OsLog::interval_with_fields`].
+    pub fn field(mut self, key: &str, value: SignpostArg<'_>) -> Self {
+        self.message.field(key, value);
+        self
+    }
+
+    /// Start the interval.
+    pub fn start(self) -> SignpostInterval<'a> {
+        SignpostInterval::new(
+            IntervalLog::Borrowed(self.log),
+            self.id.unwrap_or_else(SignpostId::next),
+            &self.name,
+            self.message.build().as_deref(),
+        )
+    }
+}
+
+/// Builder for an event, created with [`OsLog::event_builder`].
+#[cfg(feature = "std")]
+pub struct EventBuilder<'a> {
+    log: &'a OsLog,
+    name: String,
+    id: Option<SignpostId>,
+    message: SignpostMessage,
+}
+
+#[cfg(feature = "std")]
+impl EventBuilder<'_> {
+    /// Set the signpost ID, [`SignpostId::next`] by default.
+    pub fn id(mut self, id: SignpostId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Set the message of the event.
+    pub fn message<T: AsRef<str>>(mut self, message: T) -> Self {
+        self.message.set(message.as_ref());
+        self
+    }
+
+    /// Append a `key=value` field to the message, see [`OsLog::interval_with_fields`].
+    pub fn field(mut self, key: &str, value: SignpostArg<'_>) -> Self {
+        self.message.field(key, value);
+        self
+    }
+
+    /// Emit the event.
+    pub fn emit(self) {
+        let id = self.id.unwrap_or_else(SignpostId::next);
+        self.log.emit(
+            id,
+            &self.name,
+            self.message.build().as_deref(),
+            SignpostKind::Event,
+        );
+    }
+}
+
+/// Intervals that end together, in reverse order of their start, created with
+/// [`OsLog::group`].
+///
+/// Useful for a request acquiring several tracked resources at once, instead of holding a
+/// [`SignpostInterval`] per resource. Dropping the group ends the intervals that are still
+/// open, marking them as panicked while unwinding like a single interval.
+#[must_use = "dropping the group ends its intervals immediately"]
+#[cfg(feature = "std")]
+pub struct IntervalGroup<'a> {
+    log: &'a OsLog,
+    intervals: Vec<SignpostInterval<'a>>,
+}
+
+#[cfg(feature = "std")]
+impl IntervalGroup<'_> {
+    /// Start an interval in the group.
+    pub fn interval<T: AsRef<str>>(mut self, id: SignpostId, name: T) -> Self {
+        self.push(id, name);
+        self
+    }
+
+    /// Start an interval in the group, e.g. from a loop.
+    pub fn push<T: AsRef<str>>(&mut self, id: SignpostId, name: T) {
+        self.intervals.push(self.log.interval(id, name));
+    }
+
+    /// The IDs of the intervals, in the order they started.
+    pub fn ids(&self) -> Vec<SignpostId> {
+        self.intervals.iter().map(SignpostInterval::id).collect()
+    }
+
+    /// End all intervals immediately instead of when the group is dropped.
+    pub fn end(self) {}
+}
+
+#[cfg(feature = "std")]
+impl Drop for IntervalGroup<'_> {
+    fn drop(&mut self) {
+        while let Some(interval) = self.intervals.pop() {
+            drop(interval);
+        }
+    }
+}
+
+/// Message of a builder, only assembled while signposts are enabled.
+#[cfg(feature = "std")]
+struct SignpostMessage {
+    enabled: bool,
+    message: Option<String>,
+    fields: String,
+}
+
+#[cfg(feature = "std")]
+impl SignpostMessage {
+    fn new(log: &OsLog) -> Self {
+        Self {
+            enabled: log.enabled(),
+            message: None,
+            fields: String::new(),
+        }
+    }
+
+    fn set(&mut self, message: &str) {
+        if self.enabled {
+            self.message = Some(message.to_string());
+        }
+    }
+
+    fn field(&mut self, key: &str, value: SignpostArg<'_>) {
+        if self.enabled {
+            if !self.fields.is_empty() {
+                self.fields.push(' ');
+            }
+            self.fields.push_str(&format_fields(&[(key, value)]));
+        }
+    }
+
+    /// The message followed by the fields.
+    fn build(self) -> Option<String> {
+        match (self.message, self.fields.is_empty()) {
+            (message, true) => message,
+            (Some(message), false) => Some(format!("{message} {}", self.fields)),
+            (None, false) => Some(self.fields),
+        }
+    }
+}
+
+/// Subsystem and category of the global logger.
+#[cfg(feature = "std")]
+static GLOBAL_CONFIG: RwLock<Option<(String, &'static CStr)>> = RwLock::new(None);
+
+/// Incremented whenever [`GLOBAL_CONFIG`] changes, to invalidate [`CategoryLogger`] caches.
+#[cfg(feature = "std")]
+static CONFIG_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// The global logger, created on first use after configuration.
+///
+/// Published loggers are never freed, so references handed out stay valid after a reset.
+#[cfg(feature = "std")]
+static GLOBAL_LOGGER: AtomicPtr<OsLog> = AtomicPtr::new(std::ptr::null_mut());
+
+/// Process-wide kill switch for emission, see [`Signpost::set_enabled`].
+#[cfg(feature = "std")]
+static EMISSION_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Whether nested intervals are named after the intervals they're nested in, see
+/// [`Signpost::set_breadcrumbs`].
+#[cfg(feature = "std")]
+static BREADCRUMBS: AtomicBool = AtomicBool::new(false);
+
+/// Loggers registered by name with [`SignpostBuilder::logger`].
+#[cfg(feature = "std")]
+static NAMED_LOGGERS: LazyLock<RwLock<HashMap<String, &'static OsLog>>> =
+    LazyLock::new(Default::default);
+
+/// Configuration builder for signpost tracer.
+#[cfg(feature = "std")]
+pub struct Signpost {
+    subsystem: String,
+    category: &'static CStr,
+}
+
+#[cfg(feature = "std")]
+impl Signpost {
+    /// Initializes the process global signpost configuration.
+    ///
+    /// Only the first call configures the process. Later calls, e.g. from a plugin when the
+    /// host binary already configured signposts, return `Err(SignpostError::AlreadyConfigured)`
+    /// and leave the existing configuration in place. An empty subsystem or one containing a
+    /// NUL byte is rejected with `Err(SignpostError::InvalidSubsystem)`.
+    ///
+    /// To use the crate name as the subsystem, see [`configure_from_crate!`].
+    pub fn configure(subsystem: &str, category: &'static CStr) -> Result<Self, SignpostError> {
+        Self::configure_builder(subsystem, category).configure()
+    }
+
+    /// Creates a builder for the process global configuration with additional named loggers.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// Signpost::configure_builder("com.example.app", categories::POINTS_OF_INTEREST)
+    ///     .logger("networking", "com.example.app.networking", categories::POINTS_OF_INTEREST)
+    ///     .logger("storage", "com.example.app.storage", categories::DYNAMIC_TRACING)
+    ///     .configure()?;
+    ///
+    /// let _interval = interval!(logger = "networking", "request");
+    /// ```
+    pub fn configure_builder(subsystem: &str, category: &'static CStr) -> SignpostBuilder {
+        SignpostBuilder {
+            subsystem: subsystem.to_string(),
+            category,
+            loggers: Vec::new(),
+        }
+    }
+
+    /// Get a logger registered with [`SignpostBuilder::logger`].
+    pub fn logger(name: &str) -> Option<&'static OsLog> {
+        NAMED_LOGGERS
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .copied()
+    }
+
+    /// Turns all signpost emission in the process on or off at runtime, on by default.
+    ///
+    /// A kill switch for when signpost overhead is suspected to cause a problem and the
+    /// process can't be restarted. While off, [`OsLog::enabled`] is false for every logger,
+    /// so nothing is emitted and messages aren't built, regardless of Instruments. It takes
+    /// effect with the next signpost on every thread, and doesn't require configuring the
+    /// process. Intervals that began before turning emission off don't emit their end.
+    pub fn set_enabled(enabled: bool) {
+        EMISSION_ENABLED.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether signpost emission is turned on, see [`Signpost::set_enabled`].
+    pub fn is_enabled() -> bool {
+        EMISSION_ENABLED.load(Ordering::Relaxed)
+    }
+
+    /// Name intervals after the intervals they're nested in, off by default.
+    ///
+    /// Intervals, including those of the macros and `#[signpost]` functions, are named after
+    /// the innermost interval open on the same thread, e.g. `outer > inner`, which makes
+    /// deep call trees readable in the name column of Instruments. The open intervals are
+    /// tracked per thread while signposts are enabled, and an interval leaves the chain when
+    /// it ends, also while unwinding from a panic. Intervals moved to another thread before
+    /// ending leave it once an interval they're nested in ends. With the
+    /// `tracing` feature, see `TracingSubscriberBuilder::breadcrumbs` for tracing spans.
+    pub fn set_breadcrumbs(enabled: bool) {
+        BREADCRUMBS.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Suppress intervals nested deeper than `max_depth` on a thread, unlimited by default.
+    ///
+    /// Keeps recursive functions instrumented with `#[signpost]` or the macros observable
+    /// at their top levels without flooding the trace: intervals beginning while `max_depth`
+    /// intervals are open on the same thread, e.g. at recursion level `max_depth + 1`, emit
+    /// nothing. Suppressed intervals still count towards the depth, so it stays balanced
+    /// as they end. The depth is only tracked while a limit is set, and `usize::MAX`
+    /// removes the limit. Intervals moved to another thread before ending count until an
+    /// interval they're nested in ends.
+    pub fn set_max_interval_depth(max_depth: usize) {
+        depth::set_max(max_depth);
+    }
+
+    /// Switches the category of the global logger at runtime, e.g. from a debug toggle.
+    ///
+    /// Signposts using the global logger, including those of the macros and `#[signpost]`
+    /// functions, are emitted under `category` from the next signpost on. Flipping from
+    /// [`categories::POINTS_OF_INTEREST`] to [`categories::DYNAMIC_TRACING`] makes always-on
+    /// signposts recording-only.
+    ///
+    /// The switch is atomic and can happen from any thread: each signpost uses either the
+    /// previous or the new category, and other threads observe the switch with their next
+    /// signpost. Intervals that already began end on the logger they began on, so begin and
+    /// end always match. Loggers are created once per category and reused when switching
+    /// back. Loggers for a category given with `#[signpost(category = ...)]`, named loggers
+    /// and [`Signpost::scoped_subsystem`] scopes that already exist aren't affected.
+    ///
+    /// # Returns
+    /// - `Ok(())`: The category was switched
+    /// - `Err(SignpostError::NotConfigured)`: If the process isn't configured yet
+    pub fn set_category(category: &'static CStr) -> Result<(), SignpostError> {
+        let mut global = GLOBAL_CONFIG
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let Some((subsystem, current)) = global.as_mut() else {
+            return Err(SignpostError::NotConfigured);
+        };
+        *current = category;
+        CONFIG_GENERATION.fetch_add(1, Ordering::Release);
+        // Cached loggers are never freed, like the published global logger.
+        let logger = cached_logger(subsystem, category);
+        GLOBAL_LOGGER.store(std::ptr::from_ref(logger).cast_mut(), Ordering::Release);
+        Ok(())
+    }
+
+    /// Initializes the process global signpost configuration, panicking if it has already
+    /// been configured.
+    pub fn configure_or_panic(subsystem: &str, category: &'static CStr) -> Self {
+        match Self::configure(subsystem, category) {
+            Ok(config) => config,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Clears the process global signpost configuration.
+    ///
+    /// The global logger is recreated from the next configuration. Loggers handed out
+    /// before the reset remain valid, so each reset leaks one logger.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn reset() {
+        let mut global = GLOBAL_CONFIG
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        *global = None;
+        CONFIG_GENERATION.fetch_add(1, Ordering::Release);
+        GLOBAL_LOGGER.store(std::ptr::null_mut(), Ordering::Release);
+        NAMED_LOGGERS
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Configures the process global signpost state for the duration of a test.
+    ///
+    /// Callers are serialized, so tests using this can run in parallel. The previous
+    /// configuration is restored when the returned guard is dropped.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn configure_for_test(subsystem: &str, category: &'static CStr) -> TestConfigGuard {
+        static TEST_LOCK: Mutex<()> = Mutex::new(());
+        let lock = TEST_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let previous = GLOBAL_CONFIG
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        Self::reset();
+        Self::configure_or_panic(subsystem, category);
+
+        TestConfigGuard {
+            previous,
+            _lock: lock,
+        }
+    }
+}
+
+/// Checks that a subsystem can be passed to `os_log_create`.
+#[cfg(feature = "std")]
+fn validate_subsystem(subsystem: &str) -> Result<(), SignpostError> {
+    if subsystem.is_empty() || subsystem.contains('\0') {
+        return Err(SignpostError::InvalidSubsystem);
+    }
+    Ok(())
+}
+
+/// Builder for the process global configuration, see [`Signpost::configure_builder`].
+#[cfg(feature = "std")]
+pub struct SignpostBuilder {
+    subsystem: String,
+    category: &'static CStr,
+    loggers: Vec<(String, String, &'static CStr)>,
+}
+
+#[cfg(feature = "std")]
+impl SignpostBuilder {
+    /// Register a logger for `subsystem` and `category` under `name`.
+    ///
+    /// Named loggers are available through [`Signpost::logger`] and the `logger = name`
+    /// argument of the signpost macros.
+    pub fn logger(
+        mut self,
+        name: impl Into<String>,
+        subsystem: impl Into<String>,
+        category: &'static CStr,
+    ) -> Self {
+        self.loggers.push((name.into(), subsystem.into(), category));
+        self
+    }
+
+    /// Initializes the process global signpost configuration, see [`Signpost::configure`].
+    ///
+    /// # Returns
+    /// - `Ok(Signpost)`: The configuration
+    /// - `Err(SignpostError::AlreadyConfigured)`: If the process is already configured
+    /// - `Err(SignpostError::InvalidSubsystem)`: If a subsystem is empty or contains a NUL byte
+    pub fn configure(self) -> Result<Signpost, SignpostError> {
+        // Validated here since the log handles are only created on first use.
+        validate_subsystem(&self.subsystem)?;
+        for (_, subsystem, _) in &self.loggers {
+            validate_subsystem(subsystem)?;
+        }
+
+        let config = Signpost {
+            subsystem: self.subsystem,
+            category: self.category,
+        };
+
+        let mut global = GLOBAL_CONFIG
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if global.is_some() {
+            return Err(SignpostError::AlreadyConfigured);
+        }
+        *global = Some((config.subsystem.clone(), config.category));
+        CONFIG_GENERATION.fetch_add(1, Ordering::Release);
+        *NAMED_LOGGERS
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = self
+            .loggers
+            .iter()
+            .map(|(name, subsystem, category)| (name.clone(), cached_logger(subsystem, category)))
+            .collect();
+
+        Ok(config)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Signpost {
+    /// Runs `f` with signpost macros on this thread logging to `subsystem`.
+    ///
+    /// This lets library code tag its signposts with its own subsystem without changing the
+    /// global configuration of the host. See [`Signpost::scoped_subsystem`].
+    pub fn with_subsystem<R>(subsystem: &str, f: impl FnOnce() -> R) -> R {
+        let _guard = Self::scoped_subsystem(subsystem);
+        f()
+    }
+
+    /// Makes signpost macros on this thread log to `subsystem` until the guard is dropped.
+    ///
+    /// Scopes nest, the innermost one is used. The category of the global configuration is
+    /// used if configured, otherwise [`categories::POINTS_OF_INTEREST`].
+    pub fn scoped_subsystem(subsystem: &str) -> ScopedLoggerGuard {
+        let logger = subsystem_logger(subsystem);
+
+        SCOPED_LOGGERS.with_borrow_mut(|loggers| {
+            loggers.push(logger);
+            ScopedLoggerGuard {
+                depth: loggers.len() - 1,
+                _not_send: PhantomData,
+            }
+        })
+    }
+}
+
+/// The shared logger for `subsystem` with the category of the global configuration, or
+/// [`categories::POINTS_OF_INTEREST`] if unconfigured.
+#[cfg(feature = "std")]
+fn subsystem_logger(subsystem: &str) -> &'static OsLog {
+    let category = GLOBAL_CONFIG
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .map_or(categories::POINTS_OF_INTEREST, |(_, category)| category);
+    cached_logger(subsystem, category)
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    /// Loggers of the active [`Signpost::scoped_subsystem`] scopes on this thread.
+    static SCOPED_LOGGERS: RefCell<Vec<&'static OsLog>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Restores the previously scoped logger when dropped.
+///
+/// Returned by [`Signpost::scoped_subsystem`].
+#[cfg(feature = "std")]
+pub struct ScopedLoggerGuard {
+    depth: usize,
+    // Scopes are per thread, so the guard must be dropped on the thread that created it.
+    _not_send: PhantomData<*const ()>,
+}
+
+#[cfg(feature = "std")]
+impl Drop for ScopedLoggerGuard {
+    fn drop(&mut self) {
+        SCOPED_LOGGERS.with_borrow_mut(|loggers| loggers.truncate(self.depth));
+    }
+}
+
+/// Restores the previous global configuration when dropped.
+///
+/// Returned by [`Signpost::configure_for_test`].
+#[cfg(all(feature = "std", any(test, feature = "test-util")))]
+pub struct TestConfigGuard {
+    previous: Option<(String, &'static CStr)>,
+    _lock: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(all(feature = "std", any(test, feature = "test-util")))]
+impl Drop for TestConfigGuard {
+    fn drop(&mut self) {
+        Signpost::reset();
+        *GLOBAL_CONFIG
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = self.previous.take();
+        CONFIG_GENERATION.fetch_add(1, Ordering::Release);
+    }
+}
+
+/// Get the logger used by the signpost macros on this thread.
+///
+/// This is the logger of the innermost [`Signpost::scoped_subsystem`] scope, then with the
+/// `tokio` feature the logger of the current task, falling back to the global logger.
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub fn current_logger() -> &'static OsLog {
+    let scoped = SCOPED_LOGGERS.with_borrow(|loggers| loggers.last().copied());
+    #[cfg(feature = "tokio")]
+    let scoped = scoped.or_else(tokio_task::task_logger);
+    scoped.unwrap_or_else(global_logger)
+}
+
+/// Subsystem used by signposts emitted before [`Signpost::configure`].
+#[cfg(feature = "std")]
+const UNCONFIGURED_SUBSYSTEM: &str = "signpost.unconfigured";
+
+/// Whether the use of signposts before configuration was reported.
+#[cfg(feature = "std")]
+static UNCONFIGURED_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Reports the first use of signposts before [`Signpost::configure`] on stderr.
+///
+/// Only with the `warn-unconfigured` feature in debug builds, so misuse is visible during
+/// development while release builds silently use the fallback logger.
+#[cfg(feature = "std")]
+fn warn_unconfigured() {
+    if cfg!(all(feature = "warn-unconfigured", debug_assertions))
+        && !UNCONFIGURED_WARNED.swap(true, Ordering::Relaxed)
+    {
+        eprintln!(
+            "signpost: signposts are used before `Signpost::configure`, emitting to the \
+             `{UNCONFIGURED_SUBSYSTEM}` subsystem"
+        );
+    }
+}
+
+/// Get the logger registered under `name`, falling back to [`current_logger`].
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub fn named_logger(name: &str) -> &'static OsLog {
+    Signpost::logger(name).unwrap_or_else(current_logger)
+}
+
+/// Get the global logger for signpost operations.
+///
+/// Before [`Signpost::configure`] this returns a logger for the `signpost.unconfigured`
+/// subsystem instead of panicking, since library code can't control when the host
+/// configures signposts.
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub fn global_logger() -> &'static OsLog {
+    let logger = GLOBAL_LOGGER.load(Ordering::Acquire);
+    if !logger.is_null() {
+        // SAFETY: published loggers are leaked and never freed.
+        return unsafe { &*logger };
+    }
+
+    // Hold the configuration lock while publishing so a concurrent reset can't interleave.
+    let config = GLOBAL_CONFIG.read().unwrap_or_else(PoisonError::into_inner);
+    let Some((subsystem, category)) = config.as_ref() else {
+        // Not published, so a later configuration still takes effect.
+        warn_unconfigured();
+        return cached_logger(UNCONFIGURED_SUBSYSTEM, categories::POINTS_OF_INTEREST);
+    };
+
+    let logger = Box::into_raw(Box::new(OsLog::new(subsystem.clone(), category)));
+    match GLOBAL_LOGGER.compare_exchange(
+        std::ptr::null_mut(),
+        logger,
+        Ordering::AcqRel,
+        Ordering::Acquire,
+    ) {
+        // SAFETY: the logger was just leaked and is never freed.
+        Ok(_) => unsafe { &*logger },
+        Err(existing) => {
+            // SAFETY: the logger was never published, and existing ones are never freed.
+            drop(unsafe { Box::from_raw(logger) });
+            unsafe { &*existing }
+        }
+    }
+}
+
+/// Get the logger for the configured subsystem and the given category.
+///
+/// Loggers are created once per subsystem and category, and shared by all callers.
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub fn category_logger(category: &'static CStr) -> &'static OsLog {
+    let config = GLOBAL_CONFIG.read().unwrap_or_else(PoisonError::into_inner);
+    let subsystem = config.as_ref().map_or_else(
+        || {
+            warn_unconfigured();
+            UNCONFIGURED_SUBSYSTEM
+        },
+        |(subsystem, _)| subsystem,
+    );
+    cached_logger(subsystem, category)
+}
+
+/// A [`category_logger`] cached for a call site, e.g. by `#[signpost(category = ...)]`.
+///
+/// Only loggers of the configured subsystem are cached, and only until the configuration
+/// changes, so signposts emitted before [`Signpost::configure`] don't pin the fallback
+/// logger.
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub struct CategoryLogger {
+    cached: RwLock<Option<(u64, &'static OsLog)>>,
+}
+
+#[cfg(feature = "std")]
+impl CategoryLogger {
+    /// An empty cache.
+    pub const fn new() -> Self {
+        Self {
+            cached: RwLock::new(None),
+        }
+    }
+
+    /// The logger for the configured subsystem and `category`.
+    pub fn get(&self, category: &'static CStr) -> &'static OsLog {
+        let cached = *self.cached.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some((generation, logger)) = cached {
+            if generation == CONFIG_GENERATION.load(Ordering::Acquire) {
+                return logger;
+            }
+        }
+
+        // The generation is bumped while the configuration is locked for writing.
+        let config = GLOBAL_CONFIG.read().unwrap_or_else(PoisonError::into_inner);
+        let generation = CONFIG_GENERATION.load(Ordering::Acquire);
+        let Some((subsystem, _)) = config.as_ref() else {
+            warn_unconfigured();
+            return cached_logger(UNCONFIGURED_SUBSYSTEM, category);
+        };
+        let logger = cached_logger(subsystem, category);
+        *self.cached.write().unwrap_or_else(PoisonError::into_inner) = Some((generation, logger));
+        logger
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for CategoryLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the shared logger for the given subsystem and category, creating it on first use.
+#[cfg(feature = "std")]
+fn cached_logger(subsystem: &str, category: &'static CStr) -> &'static OsLog {
+    type Loggers = HashMap<(String, &'static CStr), &'static OsLog>;
+    static LOGGERS: OnceLock<Mutex<Loggers>> = OnceLock::new();
+
+    let mut loggers = LOGGERS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    loggers
+        .entry((subsystem.to_string(), category))
+        .or_insert_with(|| Box::leak(Box::new(OsLog::new(subsystem.to_string(), category))))
+}
+
+/// Emit an interval begin signpost, like Apple's `os_signpost_interval_begin`.
+///
+/// Unlike [`OsLog::interval`] no guard ends the interval: the caller is responsible for
+/// pairing it with exactly one [`interval_end`] with the same `log`, `id` and `name`, also on
+/// early returns and panics. Intervals that never end are shown as open until the end of
+/// the recording in Instruments.
+///
+/// # Examples
+/// ```ignore
+/// let id = SignpostId::next();
+/// signpost::interval_begin(&log, id, "Frame");
+/// render();
+/// signpost::interval_end(&log, id, "Frame");
+/// ```
+#[cfg(feature = "std")]
+pub fn interval_begin<T: AsRef<str>>(log: &OsLog, id: SignpostId, name: T) {
+    log.begin_interval_raw(id, name);
+}
+
+/// Emit an interval end signpost, like Apple's `os_signpost_interval_end`.
+///
+/// Ends the interval begun by [`interval_begin`] with the same `log`, `id` and `name`.
+#[cfg(feature = "std")]
+pub fn interval_end<T: AsRef<str>>(log: &OsLog, id: SignpostId, name: T) {
+    log.end_interval_raw(id, name);
+}
+
+/// Helper macro to get the current function name
+#[doc(hidden)]
+#[macro_export]
+macro_rules! function_name {
+    () => {{
+        fn f() {}
+        fn type_name_of<T>(_: T) -> &'static str {
+            std::any::type_name::<T>()
+        }
+        let name = type_name_of(f);
+        $crate::function_path(name.strip_suffix("::f").unwrap_or(name))
+    }};
+}
+
+/// Cleans up the type name of a function for use in signpost names.
+///
+/// Drops `{{closure}}` segments and generic arguments, and names trait methods after the
+/// implementing type, so `<my_crate::Foo<_> as Trait>::run::{{closure}}` becomes
+/// `my_crate::Foo::run`.
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub fn function_path(name: &str) -> std::borrow::Cow<'_, str> {
+    if !name.contains(['<', '{']) {
+        return name.into();
+    }
+
+    // Qualified paths of trait methods: keep the implementing type.
+    let mut name = name.to_string();
+    if let Some(qualified) = name.strip_prefix('<') {
+        let mut depth = 0;
+        let close = qualified.char_indices().find_map(|(idx, c)| match c {
+            '<' => {
+                depth += 1;
+                None
+            }
+            '>' if depth == 0 => Some(idx),
+            '>' => {
+                depth -= 1;
+                None
+            }
+            _ => None,
+        });
+        if let Some(close) = close {
+            let self_type = &qualified[..close];
+            let self_type = self_type
+                .find(" as ")
+                .map_or(self_type, |idx| &self_type[..idx]);
+            name = format!("{self_type}{}", &qualified[close + 1..]);
+        }
+    }
+
+    let mut path = String::with_capacity(name.len());
+    let mut depth = 0usize;
+    for c in name.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => path.push(c),
+            _ => {}
+        }
+    }
+
+    path.split("::")
+        .filter(|segment| !segment.is_empty() && *segment != "{{closure}}")
+        .collect::<Vec<_>>()
+        .join("::")
+        .into()
+}
+
+/// Builds the name for the signpost macros from the calling function and `name`.
+///
+/// With the `source_location` feature the call site is appended, resulting in
+/// `module::fn::name (file:line)`.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "source_location")]
+macro_rules! signpost_name {
+    ($name:expr) => {
+        format!(
+            "{}::{} ({}:{})",
+            $crate::function_name!(),
+            $name,
+            file!(),
+            line!()
+        )
+    };
+}
+
+/// Builds the name for the signpost macros from the calling function and `name`.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "source_location"))]
+macro_rules! signpost_name {
+    ($name:expr) => {
+        format!("{}::{}", $crate::function_name!(), $name)
+    };
+}
+
+/// Expands to the first block, or to the second one with the `disabled` feature.
+///
+/// The second block must reference the macro arguments without evaluating them, so
+/// variables only used by signposts don't cause unused warnings.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "disabled"))]
+macro_rules! compiled_in {
+    ($enabled:block, $disabled:block) => {
+        $enabled
+    };
+}
+
+/// Expands to the first block, or to the second one with the `disabled` feature.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "disabled")]
+macro_rules! compiled_in {
+    ($enabled:block, $disabled:block) => {
+        $disabled
+    };
+}
+
+/// Creates a signpost interval manually with a name.
+///
+/// # Parameters
+/// - `name`: A static string describing the operation being measured.
+///
+/// # Returns
+/// A `SignpostInterval` that will automatically emit an end signpost when dropped.
+/// Creates a signpost interval manually with a name that includes the module path.
+///
+/// # Parameters
+/// - `log` (optional): An `&OsLog` to use instead of the global logger, or
+///   `logger = "name"` for a logger registered with [`SignpostBuilder::logger`].
+/// - `name`: A string describing the operation being measured.
+///
+/// # Returns
+/// A `SignpostInterval` that will automatically emit an end signpost when dropped.
+/// The signpost name will be in the format "function_name::name".
+#[macro_export]
+macro_rules! interval {
+    (logger = $logger:expr, $name:expr) => {
+        $crate::compiled_in!(
+            {
+                let logger = $crate::named_logger($logger);
+                let full_name = $crate::signpost_name!($name);
+                logger.interval($crate::SignpostId::next(), &full_name)
+            },
+            {
+                let _ = || {
+                    let _ = (&$logger, &$name);
+                };
+                $crate::SignpostInterval::disabled()
+            }
+        )
+    };
+    ($log:expr, $name:expr) => {
+        $crate::compiled_in!(
+            {
+                let logger: &$crate::OsLog = $log;
+                let full_name = $crate::signpost_name!($name);
+                logger.interval($crate::SignpostId::next(), &full_name)
+            },
+            {
+                let _ = || {
+                    let _ = (&$log, &$name);
+                };
+                $crate::SignpostInterval::disabled()
+            }
+        )
+    };
+    ($name:expr) => {
+        $crate::compiled_in!(
+            {
+                let logger = $crate::current_logger();
+                let id = $crate::SignpostId::next();
+                let full_name = $crate::signpost_name!($name);
+                logger.interval(id, &full_name)
+            },
+            {
+                let _ = || {
+                    let _ = &$name;
+                };
+                $crate::SignpostInterval::disabled()
+            }
+        )
+    };
+}
+
+/// Creates a signpost interval manually with a name and message that includes the module path.
+///
+/// # Parameters
+/// - `log` (optional): An `&OsLog` or `logger = "name"`, see [`interval!`].
+/// - `name`: A string describing the operation being measured.
+/// - `message`: Additional information about the operation being measured.
+///
+/// # Returns
+/// A `SignpostInterval` that will automatically emit an end signpost when dropped.
+/// The signpost name will be in the format "function_name::name".
+///
+/// The name and message are only evaluated while signposts are enabled, so a message
+/// like `format!("{item}")` doesn't allocate when Instruments isn't recording.
+#[macro_export]
+macro_rules! interval_with_message {
+    (logger = $logger:expr, $name:expr, $message:expr) => {
+        $crate::compiled_in!(
+            {
+                $crate::named_logger($logger).interval_lazy($crate::SignpostId::next(), || {
+                    ($crate::signpost_name!($name), $message)
+                })
+            },
+            {
+                let _ = || {
+                    let _ = (&$logger, &$name, &$message);
+                };
+                $crate::SignpostInterval::disabled()
+            }
+        )
+    };
+    ($log:expr, $name:expr, $message:expr) => {
+        $crate::compiled_in!(
+            {
+                let logger: &$crate::OsLog = $log;
+                logger.interval_lazy($crate::SignpostId::next(), || {
+                    ($crate::signpost_name!($name), $message)
+                })
+            },
+            {
+                let _ = || {
+                    let _ = (&$log, &$name, &$message);
+                };
+                $crate::SignpostInterval::disabled()
+            }
+        )
+    };
+    ($name:expr, $message:expr) => {
+        $crate::compiled_in!(
+            {
+                $crate::current_logger().interval_lazy($crate::SignpostId::next(), || {
+                    ($crate::signpost_name!($name), $message)
+                })
+            },
+            {
+                let _ = || {
+                    let _ = (&$name, &$message);
+                };
+                $crate::SignpostInterval::disabled()
+            }
+        )
+    };
+}
+
+/// Emit a signpost event (point in time) with module path included.
+///
+/// The event name will be in the format "function_name::name".
+///
+/// # Usage
+///
+/// ```ignore
+/// event!("Something Happened");
+/// event!("User Action");
+/// event!("Error Occurred");
+/// event!(logger = "networking", "Connected");
+/// event!(&my_log, "Connected");
+/// ```
+#[macro_export]
+macro_rules! event {
+    (logger = $logger:expr, $name:expr) => {
+        $crate::compiled_in!(
+            {
+                let logger = $crate::named_logger($logger);
+                let full_name = $crate::signpost_name!($name);
+                logger.event($crate::SignpostId::next(), &full_name);
+            },
+            {
+                let _ = || {
+                    let _ = (&$logger, &$name);
+                };
+            }
+        )
+    };
+    ($log:expr, $name:expr) => {
+        $crate::compiled_in!(
+            {
+                let logger: &$crate::OsLog = $log;
+                let full_name = $crate::signpost_name!($name);
+                logger.event($crate::SignpostId::next(), &full_name);
+            },
+            {
+                let _ = || {
+                    let _ = (&$log, &$name);
+                };
+            }
+        )
+    };
+    ($name:expr) => {
+        $crate::compiled_in!(
+            {
+                let logger = $crate::current_logger();
+                let id = $crate::SignpostId::next();
+                let full_name = $crate::signpost_name!($name);
+                logger.event(id, &full_name);
+            },
+            {
+                let _ = || {
+                    let _ = &$name;
+                };
+            }
+        )
+    };
+}
+
+/// Emit a signpost event with a message and module path included.
+///
+/// The event name will be in the format "function_name::name".
+///
+/// # Usage
+///
+/// ```ignore
+/// event_with_message!("Something Happened", "Additional context");
+/// event_with_message!("User Action", "Button clicked");
+/// event_with_message!("Error Occurred", "Network timeout");
+/// event_with_message!(logger = "networking", "Request", "GET /");
+/// event_with_message!(&my_log, "Request", "GET /");
+/// ```
+#[macro_export]
+macro_rules! event_with_message {
+    (logger = $logger:expr, $name:expr, $message:expr) => {
+        $crate::compiled_in!(
+            {
+                let logger = $crate::named_logger($logger);
+                let full_name = $crate::signpost_name!($name);
+                logger.event_with_message($crate::SignpostId::next(), &full_name, $message);
+            },
+            {
+                let _ = || {
+                    let _ = (&$logger, &$name, &$message);
+                };
+            }
+        )
+    };
+    ($log:expr, $name:expr, $message:expr) => {
+        $crate::compiled_in!(
+            {
+                let logger: &$crate::OsLog = $log;
+                let full_name = $crate::signpost_name!($name);
+                logger.event_with_message($crate::SignpostId::next(), &full_name, $message);
+            },
+            {
+                let _ = || {
+                    let _ = (&$log, &$name, &$message);
+                };
+            }
+        )
+    };
+    ($name:expr, $message:expr) => {
+        $crate::compiled_in!(
+            {
+                let logger = $crate::current_logger();
+                let id = $crate::SignpostId::next();
+                let full_name = $crate::signpost_name!($name);
+                logger.event_with_message(id, &full_name, $message);
+            },
+            {
+                let _ = || {
+                    let _ = (&$name, &$message);
+                };
+            }
+        )
+    };
+}
+
+/// Configures the process with the name of the calling crate as the subsystem.
+///
+/// Expands to [`Signpost::configure`] with `env!("CARGO_PKG_NAME")` of the crate invoking
+/// the macro, so the subsystem can't drift from the crate name. The category defaults to
+/// [`categories::POINTS_OF_INTEREST`]. Use [`Signpost::configure`] for other subsystems,
+/// e.g. a bundle ID.
+///
+/// # Usage
+///
+/// ```ignore
+/// signpost::configure_from_crate!()?;
+/// signpost::configure_from_crate!(categories::DYNAMIC_TRACING)?;
+/// ```
+#[macro_export]
+macro_rules! configure_from_crate {
+    () => {
+        $crate::configure_from_crate!($crate::categories::POINTS_OF_INTEREST)
+    };
+    ($category:expr) => {
+        $crate::Signpost::configure(env!("CARGO_PKG_NAME"), $category)
+    };
+}
+
+/// Measures a block with a signpost interval and evaluates to the block's value.
+///
+/// The block is inlined rather than wrapped in a closure, so `?` and `return` inside it
+/// apply to the enclosing function. The interval ends when the block completes or exits
+/// early. The interval name will be in the format "function_name::name".
+///
+/// # Usage
+///
+/// ```ignore
+/// let total = signpost_scope!("sum", { values.iter().sum::<u64>() });
+/// let config = signpost_scope!("parse", { parse(&input)? });
+/// ```
+#[macro_export]
+macro_rules! signpost_scope {
+    ($name:expr, $body:block) => {{
+        let _interval = $crate::interval!($name);
+        $body
+    }};
+}
+
+/// Measures a block with a signpost interval and message, evaluating to the block's value.
+///
+/// See [`signpost_scope!`] for details.
+///
+/// # Usage
+///
+/// ```ignore
+/// let rows = signpost_scope_with_message!("query", "SELECT * FROM users", { run(&query)? });
+/// ```
+#[macro_export]
+macro_rules! signpost_scope_with_message {
+    ($name:expr, $message:expr, $body:block) => {{
+        let _interval = $crate::interval_with_message!($name, $message);
+        $body
+    }};
+}
+
+/// Wraps a value to measure its drop with a signpost interval.
+///
+/// Evaluates to a [`SignpostOnDrop`] that dereferences to the value. The interval name
+/// will be in the format "function_name::name", with `drop` as the default name.
+///
+/// # Usage
+///
+/// ```ignore
+/// let buffers = signpost_drop!(allocate_buffers());
+/// let cache = signpost_drop!(Cache::load()?, "drop cache");
+/// ```
+#[macro_export]
+macro_rules! signpost_drop {
+    ($value:expr) => {
+        $crate::signpost_drop!($value, "drop")
+    };
+    ($value:expr, $name:expr) => {
+        $crate::SignpostOnDrop::new(
+            $value,
+            $crate::compiled_in!({ $crate::signpost_name!($name) }, {
+                let _ = || {
+                    let _ = &$name;
+                };
+                String::new()
+            }),
+        )
+    };
+}
+
+/// Tracing subscriber integration for os_signpost.
+///
+/// This module provides a [`TracingSubscriber`] that can be used with `tracing-subscriber`
+/// to emit tracing spans and events as os_signpost intervals and events.
+#[cfg(feature = "tracing")]
+pub mod tracing_subscriber;
+
+#[cfg(feature = "tracing")]
+pub use tracing_subscriber::{NameFormat, TracingSubscriber, TracingSubscriberBuilder};
+
+/// Bridge from the `metrics` facade to signpost events.
+///
+/// This module provides a [`MetricsRecorder`] that wraps a `metrics::Recorder` and emits
+/// updates of selected metrics as measurement events.
+#[cfg(feature = "metrics")]
+pub mod metrics_recorder;
+
+#[cfg(feature = "metrics")]
+pub use metrics_recorder::{MetricsRecorder, MetricsRecorderBuilder};
+
+/// Signpost instrumentation for iterators.
+///
+/// This module provides [`SignpostIterExt`] to open a signpost interval for the
+/// processing of each item of an iterator.
+#[cfg(feature = "std")]
+pub mod iter;
+
+#[cfg(feature = "std")]
+pub use iter::{SignpostEach, SignpostIterExt};
+
+/// Signpost instrumentation for dropping values.
+///
+/// This module provides [`SignpostOnDrop`] to measure how long dropping a value takes.
+#[cfg(feature = "std")]
+pub mod on_drop;
+
+#[cfg(feature = "std")]
+pub use on_drop::SignpostOnDrop;
+
+/// Fan-out of signposts to several loggers.
+///
+/// This module provides [`TeeLog`] to emit the same signposts under several categories.
+#[cfg(feature = "std")]
+pub mod tee;
+
+#[cfg(feature = "std")]
+pub use tee::{TeeInterval, TeeLog};
+
+/// Destinations for emitted signposts.
+///
+/// This module provides the [`SignpostSink`] trait, and a `MemorySink` with the
+/// `test-util` feature to assert emitted signposts in tests.
+#[cfg(feature = "std")]
+pub mod sink;
+
+#[cfg(feature = "console")]
+pub use sink::ConsoleSink;
+#[cfg(all(feature = "std", any(test, feature = "test-util")))]
+pub use sink::MemorySink;
+#[cfg(feature = "std")]
+pub use sink::{Emission, OsSink, SignpostRecord, SignpostSink};
+
+/// Signpost instrumentation for futures.
+///
+/// This module provides [`SignpostFutureExt`] to wrap any future in a signpost interval
+/// that is open while the future is being driven to completion.
+#[cfg(feature = "async")]
+pub mod future;
+
+#[cfg(feature = "async")]
+pub use future::{Instrumented, SignpostFutureExt};
+
+#[cfg(feature = "tokio")]
+mod tokio_task;
+
+#[cfg(all(feature = "std", test))]
+mod tests {
+    use super::*;
+
+    /// A logger recording its signposts in the returned sink.
+    #[cfg(not(feature = "disabled"))]
+    pub(crate) fn memory_log() -> (OsLog, Arc<MemorySink>) {
+        let sink = Arc::new(MemorySink::new());
+        let log = OsLog::points_of_interest("test_memory").with_sink(sink.clone());
+        (log, sink)
+    }
+
+    /// Takes the signposts recorded by `sink` and compares them to `expected`, given as
+    /// `(id, name, message, kind)`.
+    #[cfg(not(feature = "disabled"))]
+    #[track_caller]
+    pub(crate) fn expect_records(
+        sink: &MemorySink,
+        expected: &[(SignpostId, &str, Option<&str>, SignpostKind)],
+    ) {
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|&(id, name, message, kind)| SignpostRecord {
+                id,
+                name: name.to_string(),
+                message: message.map(str::to_string),
+                kind,
+            })
+            .collect();
+        assert_eq!(sink.take(), expected);
+    }
+
+    #[test]
+    fn test_next_id() {
+        let first = SignpostId::next();
+        let second = SignpostId::next();
+        assert_ne!(first, second);
+        assert!(SignpostId::from_raw(first.raw()).is_ok());
+        assert!(SignpostId::from_raw(second.raw()).is_ok());
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_macros_with_log() {
+        // Doesn't need the global configuration.
+        let (log, sink) = memory_log();
+        let interval = interval!(&log, "interval");
+        let with_message = interval_with_message!(&log, "with_message", "message");
+        event!(&log, "event");
+        event_with_message!(&log, "event", "message");
+        let ids = [interval.id(), with_message.id()];
+        drop(with_message);
+        drop(interval);
+
+        let records = sink.take();
+        let interval_ids = [records[0].id, records[1].id, records[4].id, records[5].id];
+        assert_eq!(interval_ids, [ids[0], ids[1], ids[1], ids[0]]);
+        assert_ne!(ids[0], ids[1]);
+        let records: Vec<_> = records
+            .into_iter()
+            .map(|record| {
+                // Named after this function, followed by the location with `source_location`.
+                let prefix = format!("{}::", function_name!());
+                let name = record.name.strip_prefix(&prefix).unwrap();
+                let name = name.split(' ').next().unwrap().to_string();
+                (name, record.message, record.kind)
+            })
+            .collect();
+        let record = |name: &str, message: Option<&str>, kind| {
+            (name.to_string(), message.map(str::to_string), kind)
+        };
+        assert_eq!(
+            records,
+            [
+                record("interval", None, SignpostKind::IntervalBegin),
+                record("with_message", Some("message"), SignpostKind::IntervalBegin),
+                record("event", None, SignpostKind::Event),
+                record("event", Some("message"), SignpostKind::Event),
+                record("with_message", None, SignpostKind::IntervalEnd),
+                record("interval", None, SignpostKind::IntervalEnd),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_named_loggers() {
+        let _guard = Signpost::configure_for_test("test_named", categories::POINTS_OF_INTEREST);
+        Signpost::reset();
+
+        assert_eq!(
+            Signpost::configure_builder("test_named", categories::POINTS_OF_INTEREST)
+                .logger("bad", "test\0named", categories::POINTS_OF_INTEREST)
+                .configure()
+                .err(),
+            Some(SignpostError::InvalidSubsystem)
+        );
+        Signpost::configure_builder("test_named", categories::POINTS_OF_INTEREST)
+            .logger(
+                "networking",
+                "test_named.networking",
+                categories::POINTS_OF_INTEREST,
+            )
+            .logger("storage", "test_named.storage", categories::DYNAMIC_TRACING)
+            .configure()
+            .unwrap();
+
+        let networking = Signpost::logger("networking").unwrap();
+        assert_eq!(networking.subsystem(), "test_named.networking");
+        let storage = Signpost::logger("storage").unwrap();
+        assert_eq!(storage.category(), categories::DYNAMIC_TRACING);
+        assert!(Signpost::logger("rendering").is_none());
+        assert!(std::ptr::eq(named_logger("networking"), networking));
+        assert!(std::ptr::eq(named_logger("rendering"), global_logger()));
+
+        let _interval = interval!(logger = "networking", "request");
+        let _interval = interval_with_message!(logger = "storage", "write", "file");
+        event!(logger = "networking", "connected");
+        event_with_message!(logger = "rendering", "frame", "fallback");
+
+        Signpost::reset();
+        assert!(Signpost::logger("networking").is_none());
+    }
+
+    #[test]
+    fn test_scoped_subsystem() {
+        let _config = Signpost::configure_for_test("test_scoped", categories::POINTS_OF_INTEREST);
+        assert!(std::ptr::eq(current_logger(), global_logger()));
+
+        let value = Signpost::with_subsystem("com.x.plugin", || {
+            assert_eq!(current_logger().subsystem(), "com.x.plugin");
+            {
+                let _inner = Signpost::scoped_subsystem("com.x.inner");
+                assert_eq!(current_logger().subsystem(), "com.x.inner");
+                event!("inner");
+            }
+            assert_eq!(current_logger().subsystem(), "com.x.plugin");
+            std::thread::spawn(|| assert_eq!(current_logger().subsystem(), "test_scoped"))
+                .join()
+                .unwrap();
+            42
+        });
+
+        assert_eq!(value, 42);
+        assert_eq!(current_logger().subsystem(), "test_scoped");
+    }
+
+    #[test]
+    fn test_configuration() {
+        let _guard = Signpost::configure_for_test("test_app", categories::POINTS_OF_INTEREST);
+
+        // Test that we can't init the trace twice.
+        assert!(matches!(
+            Signpost::configure("another_app", categories::POINTS_OF_INTEREST),
+            Err(SignpostError::AlreadyConfigured)
+        ));
+        std::panic::catch_unwind(|| {
+            Signpost::configure_or_panic("another_app", categories::POINTS_OF_INTEREST);
+        })
+        .expect_err("Should panic when configuring twice");
+        assert_eq!(global_logger().subsystem(), "test_app");
+    }
+
+    #[test]
+    fn test_configure_validates_subsystem() {
+        let _guard = Signpost::configure_for_test("test_validate", categories::POINTS_OF_INTEREST);
+        Signpost::reset();
+
+        for subsystem in ["", "test\0validate"] {
+            assert_eq!(
+                Signpost::configure(subsystem, categories::POINTS_OF_INTEREST).err(),
+                Some(SignpostError::InvalidSubsystem)
+            );
+        }
+        // Rejected configurations leave the process unconfigured.
+        assert_eq!(global_logger().subsystem(), UNCONFIGURED_SUBSYSTEM);
+    }
+
+    #[test]
+    fn test_reset() {
+        let guard = Signpost::configure_for_test("test_reset", categories::POINTS_OF_INTEREST);
+        let logger = global_logger();
+        assert_eq!(logger.subsystem(), "test_reset");
+        drop(guard);
+
+        let _guard = Signpost::configure_for_test("test_reset_again", categories::DYNAMIC_TRACING);
+        assert_eq!(global_logger().subsystem(), "test_reset_again");
+        assert_eq!(global_logger().category(), categories::DYNAMIC_TRACING);
+        // Loggers handed out before the reset stay valid.
+        assert_eq!(logger.subsystem(), "test_reset");
+    }
+
+    #[test]
+    fn test_set_category() {
+        let _guard = Signpost::configure_for_test("test_category", categories::POINTS_OF_INTEREST);
+        let interval = interval!("before");
+
+        Signpost::set_category(categories::DYNAMIC_TRACING).unwrap();
+        let logger = global_logger();
+        assert_eq!(logger.subsystem(), "test_category");
+        assert_eq!(logger.category(), categories::DYNAMIC_TRACING);
+        // Intervals end on the logger they began on.
+        assert_eq!(interval.log.category(), categories::POINTS_OF_INTEREST);
+
+        Signpost::set_category(categories::POINTS_OF_INTEREST).unwrap();
+        assert_eq!(global_logger().category(), categories::POINTS_OF_INTEREST);
+        Signpost::set_category(categories::DYNAMIC_TRACING).unwrap();
+        assert!(std::ptr::eq(global_logger(), logger));
+
+        Signpost::reset();
+        assert_eq!(
+            Signpost::set_category(categories::DYNAMIC_TRACING),
+            Err(SignpostError::NotConfigured)
+        );
+    }
+
+    #[test]
+    fn test_bindgen_integration() {
+        // Test that os_log_t is a pointer type from generated bindings
+        let null_log: sys::os_log_t = std::ptr::null_mut();
+        assert!(null_log.is_null());
+
+        // Test that os_signpost_id_t is u64 from generated bindings
+        let id: sys::os_signpost_id_t = 42;
+        assert_eq!(id, 42u64);
+
+        // Test that os_signpost_type_t is u8 from generated bindings
+        let signpost_type: sys::os_signpost_type_t = sys::SIGNPOST_TYPE_EVENT;
+        assert_eq!(signpost_type, 0u8);
+
+        // Verify generated constants have expected values (matching Apple's headers)
+        assert_eq!(sys::SIGNPOST_TYPE_EVENT, 0);
+        assert_eq!(sys::SIGNPOST_TYPE_INTERVAL_BEGIN, 1);
+        assert_eq!(sys::SIGNPOST_TYPE_INTERVAL_END, 2);
+    }
+
+    #[test]
+    fn test_signpost_kind_conversion() {
+        let kinds = [
+            (SignpostKind::Event, sys::SIGNPOST_TYPE_EVENT),
+            (
+                SignpostKind::IntervalBegin,
+                sys::SIGNPOST_TYPE_INTERVAL_BEGIN,
+            ),
+            (SignpostKind::IntervalEnd, sys::SIGNPOST_TYPE_INTERVAL_END),
+        ];
+        for (kind, raw) in kinds {
+            assert_eq!(u8::from(kind), raw);
+            assert_eq!(kind as u8, raw);
+            assert_eq!(SignpostKind::try_from(raw), Ok(kind));
+        }
+        assert_eq!(SignpostKind::try_from(3), Err(SignpostError::InvalidKind));
+    }
+
+    #[test]
+    fn test_unconfigured_logger() {
+        let _guard =
+            Signpost::configure_for_test("test_unconfigured", categories::POINTS_OF_INTEREST);
+        Signpost::reset();
+
+        assert_eq!(global_logger().subsystem(), UNCONFIGURED_SUBSYSTEM);
+        assert_eq!(
+            category_logger(categories::DYNAMIC_TRACING).subsystem(),
+            UNCONFIGURED_SUBSYSTEM
+        );
+        event!("before configure");
+        assert_eq!(
+            UNCONFIGURED_WARNED.load(Ordering::Relaxed),
+            cfg!(all(feature = "warn-unconfigured", debug_assertions))
+        );
+
+        // The fallback doesn't prevent configuring afterwards.
+        Signpost::configure_or_panic("test_unconfigured", categories::POINTS_OF_INTEREST);
+        assert_eq!(global_logger().subsystem(), "test_unconfigured");
+    }
+
+    #[test]
+    fn test_category_logger_cache() {
+        static LOGGER: CategoryLogger = CategoryLogger::new();
+        let _guard =
+            Signpost::configure_for_test("test_category_cache", categories::POINTS_OF_INTEREST);
+        Signpost::reset();
+
+        // The fallback used before configuring isn't cached.
+        let logger = LOGGER.get(categories::DYNAMIC_TRACING);
+        assert_eq!(logger.subsystem(), UNCONFIGURED_SUBSYSTEM);
+        Signpost::configure_or_panic("test_category_cache", categories::POINTS_OF_INTEREST);
+        let logger = LOGGER.get(categories::DYNAMIC_TRACING);
+        assert_eq!(logger.subsystem(), "test_category_cache");
+        assert_eq!(logger.category(), categories::DYNAMIC_TRACING);
+        assert!(std::ptr::eq(
+            LOGGER.get(categories::DYNAMIC_TRACING),
+            logger
+        ));
+
+        // Nor is the configured one kept after a reset.
+        Signpost::reset();
+        Signpost::configure_or_panic("test_category_cache.next", categories::POINTS_OF_INTEREST);
+        let logger = LOGGER.get(categories::DYNAMIC_TRACING);
+        assert_eq!(logger.subsystem(), "test_category_cache.next");
+    }
+
+    #[test]
+    fn test_error_types() {
+        let error = SignpostError::NotConfigured;
+        let error_msg = format!("{}", error);
+        assert!(error_msg.contains("initialized"));
+
+        let error = SignpostError::InvalidScope;
+        assert_eq!(format!("{}", error), "Invalid scope for operation");
+
+        let error = SignpostError::InvalidId;
+        assert_eq!(format!("{}", error), "Invalid signpost ID");
+
+        let error = SignpostError::AlreadyConfigured;
+        assert_eq!(format!("{}", error), "Signpost already configured");
+
+        let error = SignpostError::InvalidSubsystem;
+        assert_eq!(format!("{}", error), "Invalid signpost subsystem");
+
+        let error = SignpostError::InvalidKind;
+        assert_eq!(format!("{}", error), "Invalid signpost type");
+    }
+
+    #[test]
+    fn test_id_from_raw() {
+        assert_eq!(SignpostId::from_raw(0), Err(SignpostError::InvalidId));
+        assert_eq!(
+            SignpostId::from_raw(u64::MAX),
+            Err(SignpostError::InvalidId)
+        );
+        assert_eq!(SignpostId::from_raw(42).map(SignpostId::raw), Ok(42));
+    }
+
+    #[test]
+    fn test_id_display_parse() {
+        let id = SignpostId::from_raw(0xbeef).unwrap();
+        assert_eq!(id.to_string(), "0xbeef");
+        assert_eq!(format!("{id:x} {id:#x} {id:08x}"), "beef 0xbeef 0000beef");
+
+        assert_eq!(id.to_string().parse(), Ok(id));
+        assert_eq!("BEEF".parse(), Ok(id));
+        assert_eq!("0x0".parse::<SignpostId>(), Err(SignpostError::InvalidId));
+        assert_eq!(
+            "0xffffffffffffffff".parse::<SignpostId>(),
+            Err(SignpostError::InvalidId)
+        );
+        assert_eq!("xyz".parse::<SignpostId>(), Err(SignpostError::InvalidId));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_id_serde() {
+        let id = SignpostId::from_raw(42).unwrap();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "42");
+        assert_eq!(serde_json::from_str::<SignpostId>(&json).unwrap(), id);
+        assert!(serde_json::from_str::<SignpostId>("0").is_err());
+    }
+
+    #[test]
+    fn test_id_from_hash() {
+        let log = OsLog::points_of_interest("test_hash");
+        let id = SignpostId::from_hash(&log, "request-1");
+        assert_eq!(id, SignpostId::from_hash(&log, "request-1"));
+        assert_ne!(id, SignpostId::from_hash(&log, "request-2"));
+        assert_ne!(
+            id,
+            SignpostId::from_hash(&OsLog::dynamic_tracing("test_hash"), "request-1")
+        );
+        assert!(SignpostId::from_raw(id.raw()).is_ok());
+
+        assert_eq!(fold_reserved(0), 1);
+        assert_eq!(fold_reserved(u64::MAX), u64::MAX - 1);
+        assert_eq!(fold_reserved(42), 42);
+    }
+
+    #[test]
+    fn test_scope() {
+        let log = OsLog::new("test_scope".to_string(), categories::POINTS_OF_INTEREST);
+        assert_eq!(log.scope(), SignpostScope::Process);
+
+        let log = log.with_scope(SignpostScope::Thread);
+        assert_eq!(log.scope(), SignpostScope::Thread);
+        let value = 42;
+        assert!(SignpostId::from_pointer(&log, &value).is_ok());
+
+        let log = log.with_scope(SignpostScope::System);
+        assert_eq!(
+            SignpostId::from_pointer(&log, &value),
+            Err(SignpostError::InvalidScope)
+        );
+    }
+
+    #[test]
+    fn test_category_constructors() {
+        let log = OsLog::points_of_interest("test_categories");
+        assert_eq!(log.category(), categories::POINTS_OF_INTEREST);
+        let log = OsLog::dynamic_tracing(String::from("test_categories"));
+        assert_eq!(log.category(), categories::DYNAMIC_TRACING);
+        let log = OsLog::dynamic_stack_tracing("test_categories");
+        assert_eq!(log.category(), categories::DYNAMIC_STACK_TRACING);
+        assert_eq!(log.subsystem(), "test_categories");
+    }
+
+    #[test]
+    fn test_recording() {
+        let log = OsLog::points_of_interest("test_recording");
+        assert!(std::ptr::eq(
+            cached_logger("test_recording", categories::DYNAMIC_TRACING),
+            cached_logger("test_recording", categories::DYNAMIC_TRACING)
+        ));
+        assert_eq!(
+            log.recording(),
+            cached_logger("test_recording", categories::DYNAMIC_TRACING).enabled()
+        );
+        let log = OsLog::dynamic_tracing("test_recording");
+        assert_eq!(log.recording(), log.enabled());
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_recording_sink() {
+        // The dynamic tracing logger checked by `recording` asks the sink of this logger.
+        let sink = Arc::new(MemorySink::new());
+        let log = OsLog::points_of_interest("test_recording_sink").with_sink(sink.clone());
+        assert!(log.recording());
+        sink.set_enabled(false);
+        assert!(!log.recording());
+    }
+
+    #[test]
+    fn test_custom_category() {
+        const CATEGORY: &CStr = categories::custom!("my.category");
+        assert_eq!(CATEGORY, c"my.category");
+        let log = OsLog::new(
+            "test_categories".to_string(),
+            categories::custom!("my.category"),
+        );
+        assert_eq!(log.category(), c"my.category");
+    }
+
+    #[test]
+    fn test_builder() {
+        let log = OsLog::builder("test_builder")
+            .category(categories::DYNAMIC_TRACING)
+            .scope(SignpostScope::Thread)
+            .enabled_cache(Duration::from_secs(1))
+            .build()
+            .unwrap();
+        assert_eq!(log.subsystem(), "test_builder");
+        assert_eq!(log.category(), categories::DYNAMIC_TRACING);
+        assert_eq!(log.scope(), SignpostScope::Thread);
+        assert!(log.enabled_cache.is_some());
+
+        assert_eq!(
+            OsLog::builder("test\0builder").build().unwrap_err(),
+            SignpostError::InvalidSubsystem
+        );
+        assert_eq!(
+            OsLog::builder("").build().unwrap_err(),
+            SignpostError::InvalidSubsystem
+        );
+    }
+
+    #[test]
+    fn test_clone_shares_handle() {
+        let log = OsLog::new("test_clone".to_string(), categories::POINTS_OF_INTEREST)
+            .with_enabled_cache(Duration::from_secs(60));
+        let clone = log.clone().with_scope(SignpostScope::Thread);
+
+        assert!(Arc::ptr_eq(&log.inner, &clone.inner));
+        assert_eq!(log.get(), clone.get());
+        assert_eq!(clone.subsystem(), "test_clone");
+        assert_eq!(clone.category(), categories::POINTS_OF_INTEREST);
+        assert_eq!(log.scope(), SignpostScope::Process);
+
+        // Loggers can be handed to other threads.
+        std::thread::spawn(move || clone.enabled()).join().unwrap();
+        assert!(!log.enabled_cache.as_ref().unwrap().is_stale());
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_enabled_cache() {
+        let (log, sink) = memory_log();
+        let log = log.with_enabled_cache(Duration::from_secs(60));
+        let cache = log.enabled_cache.as_ref().unwrap();
+        assert!(cache.is_stale());
+
+        assert!(log.enabled());
+        assert!(!cache.is_stale());
+
+        // The cached state is kept until refreshed.
+        sink.set_enabled(false);
+        assert!(log.enabled());
+        assert!(!log.refresh_enabled());
+        assert!(!log.enabled());
+        let id = SignpostId::next();
+        log.event(id, "disabled");
+        expect_records(&sink, &[]);
+
+        sink.set_enabled(true);
+        assert!(!log.enabled());
+        assert!(log.refresh_enabled());
+        assert!(log.enabled());
+        log.event(id, "enabled");
+        expect_records(&sink, &[(id, "enabled", None, SignpostKind::Event)]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interval_end_once() {
+        let (log, sink) = memory_log();
+        let mut interval = log.interval(SignpostId::generate(&log), "interval");
+
+        assert!(interval.end_internal(None));
+        // Neither an explicit end nor the drop emit a second end signpost.
+        assert!(!interval.end_internal(Some("again")));
+        interval.end_with_message("done");
+        let kinds: Vec<_> = sink.take().iter().map(|record| record.kind).collect();
+        assert_eq!(
+            kinds,
+            [SignpostKind::IntervalBegin, SignpostKind::IntervalEnd]
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_end_message() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::next();
+
+        let mut interval = log.interval(id, "dropped");
+        interval.set_end_message("pending");
+        interval.set_end_message("hit");
+        drop(interval);
+        let mut interval = log.interval(id, "ended");
+        interval.set_end_message("miss");
+        interval.end();
+        let mut interval = log.interval(id, "explicit");
+        interval.set_end_message("miss");
+        interval.end_with_message("error");
+
+        expect_records(
+            &sink,
+            &[
+                (id, "dropped", None, SignpostKind::IntervalBegin),
+                (id, "dropped", Some("hit"), SignpostKind::IntervalEnd),
+                (id, "ended", None, SignpostKind::IntervalBegin),
+                (id, "ended", Some("miss"), SignpostKind::IntervalEnd),
+                (id, "explicit", None, SignpostKind::IntervalBegin),
+                (id, "explicit", Some("error"), SignpostKind::IntervalEnd),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_annotate() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::next();
+
+        let mut interval = log.interval(id, "request");
+        interval.annotate("method=GET");
+        interval.annotate("status=200");
+        drop(interval);
+        let mut interval = log.interval(id, "request");
+        interval.annotate("method=POST");
+        interval.end_with_message("failed");
+
+        expect_records(
+            &sink,
+            &[
+                (id, "request", None, SignpostKind::IntervalBegin),
+                (
+                    id,
+                    "request",
+                    Some("method=GET status=200"),
+                    SignpostKind::IntervalEnd,
+                ),
+                (id, "request", None, SignpostKind::IntervalBegin),
+                (
+                    id,
+                    "request",
+                    Some("failed method=POST"),
+                    SignpostKind::IntervalEnd,
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_slow_threshold() {
+        let (log, sink) = memory_log();
+        assert_eq!(log.slow_threshold(), None);
+        let slow_log = log.clone().with_slow_threshold(Duration::ZERO);
+        let fast_log = log.clone().with_slow_threshold(Duration::from_secs(3600));
+        let id = SignpostId::next();
+
+        let interval = slow_log.interval(id, "slow");
+        std::thread::sleep(Duration::from_millis(1));
+        drop(interval);
+        fast_log.interval(id, "fast").end();
+        log.interval(id, "default").end();
+
+        expect_records(
+            &sink,
+            &[
+                (id, "slow", None, SignpostKind::IntervalBegin),
+                (id, "slow", Some("slow"), SignpostKind::Event),
+                (id, "slow", None, SignpostKind::IntervalEnd),
+                (id, "fast", None, SignpostKind::IntervalBegin),
+                (id, "fast", None, SignpostKind::IntervalEnd),
+                (id, "default", None, SignpostKind::IntervalBegin),
+                (id, "default", None, SignpostKind::IntervalEnd),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interval_enabled_mid_interval() {
+        let (log, sink) = memory_log();
+
+        // Recording starts after the interval began: the end would be an orphan.
+        sink.set_enabled(false);
+        let mut interval = log.interval(SignpostId::next(), "interval");
+        sink.set_enabled(true);
+        assert!(log.enabled());
+        assert!(!interval.end_internal(None));
+        assert!(sink.take().is_empty());
+
+        // Recording stops before the interval ends: the end is still emitted.
+        let mut interval = log.interval(SignpostId::next(), "interval");
+        assert!(interval.started_at.is_some());
+        sink.set_enabled(false);
+        assert!(interval.end_internal(None));
+        // The sink no longer records it, like a consumer that stopped recording.
+        let kinds: Vec<_> = sink.take().iter().map(|record| record.kind).collect();
+        assert_eq!(kinds, [SignpostKind::IntervalBegin]);
+    }
+
+    #[test]
+    #[cfg(feature = "disabled")]
+    fn test_disabled() {
+        let log = OsLog::new("test_disabled".to_string(), categories::POINTS_OF_INTEREST);
+        assert!(!log.enabled());
+        assert!(!log.refresh_enabled());
+
+        let name = String::from("unused otherwise");
+        event!(name);
+        let interval = interval_with_message!("interval", name);
+        assert_eq!(interval.elapsed(), Duration::ZERO);
+        assert_eq!(signpost_scope!("scope", { 1 }), 1);
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interval_with_message_deferred() {
+        let evaluated = std::cell::Cell::new(0);
+        let message = || {
+            evaluated.set(evaluated.get() + 1);
+            String::from("message")
+        };
+        let (log, sink) = memory_log();
+
+        sink.set_enabled(false);
+        let interval = interval_with_message!(&log, "interval", message());
+        assert!(interval.started_at.is_none());
+        assert_eq!(evaluated.get(), 0);
+        drop(interval);
+
+        sink.set_enabled(true);
+        let interval = interval_with_message!(&log, "interval", message());
+        assert!(interval.started_at.is_some());
+        assert_eq!(evaluated.get(), 1);
+        expect_records(
+            &sink,
+            &[(
+                interval.id(),
+                &interval.name,
+                Some("message"),
+                SignpostKind::IntervalBegin,
+            )],
+        );
+    }
 
-    GLOBAL_LOGGER.get_or_init(|| {
-        if let Some((subsystem, category)) = GLOBAL_CONFIG.get() {
-            OsLog::new(subsystem.clone(), category)
-        } else {
-            panic!("Double Signpost config initialization");
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_event_fmt() {
+        struct Counted<'a>(&'a AtomicU64);
+        impl fmt::Display for Counted<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                f.write_str("value")
+            }
         }
-    })
-}
 
-/// Helper macro to get the current function name
-#[doc(hidden)]
-#[macro_export]
-macro_rules! function_name {
-    () => {{
-        fn f() {}
-        fn type_name_of<T>(_: T) -> &'static str {
-            std::any::type_name::<T>()
+        let (log, sink) = memory_log();
+        let count = AtomicU64::new(0);
+        let id = SignpostId::next();
+        log.event_fmt(id, "event", format_args!("{}", Counted(&count)));
+        log.event_fmt(id, "event", format_args!("static"));
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+        expect_records(
+            &sink,
+            &[
+                (id, "event", Some("value"), SignpostKind::Event),
+                (id, "event", Some("static"), SignpostKind::Event),
+            ],
+        );
+
+        // Only formatted when enabled.
+        sink.set_enabled(false);
+        log.event_fmt(id, "event", format_args!("{}", Counted(&count)));
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+        expect_records(&sink, &[]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_fields() {
+        let (log, sink) = memory_log();
+        let fields = [
+            ("size", SignpostArg::U64(1024)),
+            ("status", SignpostArg::Str("ok")),
+        ];
+
+        // Only formatted when enabled.
+        sink.set_enabled(false);
+        let interval = log.interval_with_fields(SignpostId::next(), "interval", &fields);
+        assert_eq!(interval.message, None);
+        drop(interval);
+
+        sink.set_enabled(true);
+        let id = SignpostId::next();
+        let interval = log.interval_with_fields(id, "interval", &fields);
+        log.event_with_fields(interval.id(), "event", &fields);
+        drop(interval);
+        let message = Some("size=1024 status=ok");
+        expect_records(
+            &sink,
+            &[
+                (id, "interval", message, SignpostKind::IntervalBegin),
+                (id, "event", message, SignpostKind::Event),
+                (id, "interval", None, SignpostKind::IntervalEnd),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interval_events() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::generate(&log);
+        let interval = log.interval(id, "interval");
+
+        assert_eq!(interval.id(), id);
+        assert!(std::ptr::eq(&*interval.log, &log));
+        interval.event("checkpoint");
+        interval.event_with_message("checkpoint", "halfway");
+        drop(interval);
+
+        // Events share the ID of the interval, so Instruments shows them in its lane.
+        expect_records(
+            &sink,
+            &[
+                (id, "interval", None, SignpostKind::IntervalBegin),
+                (id, "checkpoint", None, SignpostKind::Event),
+                (id, "checkpoint", Some("halfway"), SignpostKind::Event),
+                (id, "interval", None, SignpostKind::IntervalEnd),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_function_name() {
+        struct Wrapper<T>(T);
+
+        impl<T> Wrapper<T> {
+            fn name(&self) -> std::borrow::Cow<'static, str> {
+                function_name!()
+            }
         }
-        let name = type_name_of(f);
-        name.strip_suffix("::f").unwrap_or(name)
-    }};
-}
 
-/// Creates a signpost interval manually with a name.
-///
-/// # Parameters
-/// - `name`: A static string describing the operation being measured.
-///
-/// # Returns
-/// A `SignpostInterval` that will automatically emit an end signpost when dropped.
-/// Creates a signpost interval manually with a name that includes the module path.
-///
-/// # Parameters
-/// - `name`: A string describing the operation being measured.
-///
-/// # Returns
-/// A `SignpostInterval` that will automatically emit an end signpost when dropped.
-/// The signpost name will be in the format "function_name::name".
-#[macro_export]
-macro_rules! interval {
-    ($name:expr) => {{
-        let logger = $crate::global_logger();
-        let id = $crate::SignpostId::generate(logger);
-        let full_name = format!("{}::{}", $crate::function_name!(), $name);
-        logger.interval(id, &full_name)
-    }};
-}
+        impl<T> std::fmt::Display for Wrapper<T> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&function_name!())
+            }
+        }
 
-/// Creates a signpost interval manually with a name and message that includes the module path.
-///
-/// # Parameters
-/// - `name`: A string describing the operation being measured.
-/// - `message`: Additional information about the operation being measured.
-///
-/// # Returns
-/// A `SignpostInterval` that will automatically emit an end signpost when dropped.
-/// The signpost name will be in the format "function_name::name".
-#[macro_export]
-macro_rules! interval_with_message {
-    ($name:expr, $message:expr) => {{
-        let logger = $crate::global_logger();
-        let id = $crate::SignpostId::generate(logger);
-        let full_name = format!("{}::{}", $crate::function_name!(), $name);
-        logger.interval_with_message(id, &full_name, $message)
-    }};
-}
+        fn generic<T: Default>() -> std::borrow::Cow<'static, str> {
+            let _ = T::default();
+            function_name!()
+        }
 
-/// Emit a signpost event (point in time) with module path included.
-///
-/// The event name will be in the format "function_name::name".
-///
-/// # Usage
-///
-/// ```ignore
-/// event!("Something Happened");
-/// event!("User Action");
-/// event!("Error Occurred");
-/// ```
-#[macro_export]
-macro_rules! event {
-    ($name:expr) => {{
-        let logger = $crate::global_logger();
-        let id = $crate::SignpostId::generate(logger);
-        let full_name = format!("{}::{}", $crate::function_name!(), $name);
-        logger.event(id, &full_name);
-    }};
-}
+        let closure = || function_name!();
+        assert_eq!(closure(), "signpost::tests::test_function_name");
+        assert_eq!(
+            generic::<u32>(),
+            "signpost::tests::test_function_name::generic"
+        );
+        assert_eq!(
+            Wrapper(1).name(),
+            "signpost::tests::test_function_name::Wrapper::name"
+        );
+        assert_eq!(
+            Wrapper(1).to_string(),
+            "signpost::tests::test_function_name::Wrapper::fmt"
+        );
 
-/// Emit a signpost event with a message and module path included.
-///
-/// The event name will be in the format "function_name::name".
-///
-/// # Usage
-///
-/// ```ignore
-/// event_with_message!("Something Happened", "Additional context");
-/// event_with_message!("User Action", "Button clicked");
-/// event_with_message!("Error Occurred", "Network timeout");
-/// ```
-#[macro_export]
-macro_rules! event_with_message {
-    ($name:expr, $message:expr) => {{
-        let logger = $crate::global_logger();
-        let id = $crate::SignpostId::generate(logger);
-        let full_name = format!("{}::{}", $crate::function_name!(), $name);
-        logger.event_with_message(id, &full_name, $message);
-    }};
-}
+        assert_eq!(function_path("my_crate::run"), "my_crate::run");
+        assert_eq!(
+            function_path(
+                "<my_crate::Foo<my_crate::Bar<_>> as my_crate::Trait<u8>>::run::{{closure}}"
+            ),
+            "my_crate::Foo::run"
+        );
+        assert_eq!(
+            function_path("my_crate::run::{{closure}}::{{closure}}"),
+            "my_crate::run"
+        );
+    }
 
-/// Tracing subscriber integration for os_signpost.
-///
-/// This module provides a [`TracingSubscriber`] that can be used with `tracing-subscriber`
-/// to emit tracing spans and events as os_signpost intervals and events.
-#[cfg(feature = "tracing")]
-pub mod tracing_subscriber;
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interval_builder() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::next();
+        let interval = log
+            .interval_builder("interval")
+            .id(id)
+            .message("message")
+            .field("size", SignpostArg::U64(1))
+            .start();
+        assert_eq!(interval.id(), id);
+        assert_eq!(interval.name, "interval");
+        assert!(std::ptr::eq(&*interval.log, &log));
+        drop(interval);
 
-#[cfg(feature = "tracing")]
-pub use tracing_subscriber::TracingSubscriber;
+        let interval = log.interval_builder("default").start();
+        assert_ne!(interval.id(), id);
+        let default_id = interval.id();
+        drop(interval);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        log.event_builder("event")
+            .id(id)
+            .field("status", SignpostArg::Str("ok"))
+            .emit();
+        expect_records(
+            &sink,
+            &[
+                (
+                    id,
+                    "interval",
+                    Some("message size=1"),
+                    SignpostKind::IntervalBegin,
+                ),
+                (id, "interval", None, SignpostKind::IntervalEnd),
+                (default_id, "default", None, SignpostKind::IntervalBegin),
+                (default_id, "default", None, SignpostKind::IntervalEnd),
+                (id, "event", Some("status=ok"), SignpostKind::Event),
+            ],
+        );
+    }
 
     #[test]
-    fn test_configuration() {
-        Signpost::configure("test_app", categories::POINTS_OF_INTEREST);
+    fn test_signpost_message() {
+        let message = |enabled, text: Option<&str>, fields: &[(&str, SignpostArg<'_>)]| {
+            let mut message = SignpostMessage {
+                enabled,
+                message: None,
+                fields: String::new(),
+            };
+            if let Some(text) = text {
+                message.set(text);
+            }
+            for (key, value) in fields {
+                message.field(key, *value);
+            }
+            message.build()
+        };
+        let fields = [("a", SignpostArg::U64(1)), ("b", SignpostArg::I64(-2))];
 
-        // Test that we can't init the trace twice.
-        std::panic::catch_unwind(|| {
-            Signpost::configure("another_app", categories::POINTS_OF_INTEREST);
+        assert_eq!(message(true, None, &[]), None);
+        assert_eq!(message(true, Some("text"), &[]).as_deref(), Some("text"));
+        assert_eq!(message(true, None, &fields).as_deref(), Some("a=1 b=-2"));
+        assert_eq!(
+            message(true, Some("text"), &fields).as_deref(),
+            Some("text a=1 b=-2")
+        );
+        assert_eq!(message(false, Some("text"), &fields), None);
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_emit_raw() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::next();
+
+        log.emit_raw(id, c"raw", None, SignpostKind::IntervalBegin);
+        log.emit_raw(id, c"raw", Some(c"message"), SignpostKind::Event);
+        log.emit_raw(id, c"raw", None, SignpostKind::IntervalEnd);
+        expect_records(
+            &sink,
+            &[
+                (id, "raw", None, SignpostKind::IntervalBegin),
+                (id, "raw", Some("message"), SignpostKind::Event),
+                (id, "raw", None, SignpostKind::IntervalEnd),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_event_cstr() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::next();
+
+        log.event_cstr(id, c"checkpoint");
+        expect_records(&sink, &[(id, "checkpoint", None, SignpostKind::Event)]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interval_with_parent() {
+        let (log, sink) = memory_log();
+        let parent = log.interval(SignpostId::from_raw(0x2a).unwrap(), "parent");
+        sink.set_enabled(false);
+        let child = log.interval_with_parent(SignpostId::next(), "child", parent.id());
+        assert_eq!(child.message, None);
+
+        sink.set_enabled(true);
+        let child = log.interval_with_parent(SignpostId::next(), "child", parent.id());
+        assert_eq!(child.message.as_deref(), Some("parent=0x2a"));
+        let begin = sink.records().pop().unwrap();
+        assert_eq!(begin.id, child.id());
+        assert_eq!(begin.kind, SignpostKind::IntervalBegin);
+        assert_eq!(begin.message.as_deref(), Some("parent=0x2a"));
+    }
+
+    #[test]
+    fn test_dso_handle() {
+        static IMAGE: u8 = 0;
+        let log = OsLog::new("test_dso".to_string(), categories::POINTS_OF_INTEREST);
+        let default = log.dso_handle();
+        assert!(!default.is_null());
+
+        let image = (&raw const IMAGE).cast::<c_void>();
+        let log = unsafe { log.with_dso_handle(image) };
+        assert_eq!(log.dso_handle(), image);
+        assert_eq!(log.clone().dso_handle(), image);
+        let log = unsafe { log.with_dso_handle(std::ptr::null()) };
+        assert_eq!(log.dso_handle(), default);
+    }
+
+    #[test]
+    fn test_raw_handle() {
+        let log = OsLog::new("test_raw".to_string(), categories::POINTS_OF_INTEREST);
+        let handle = log.as_raw_handle();
+        assert_eq!(log.as_raw_handle(), handle);
+
+        let wrapped = unsafe { OsLog::from_raw_handle(handle) };
+        assert_eq!(wrapped.as_raw_handle(), handle);
+        assert_eq!(wrapped.clone().as_raw_handle(), handle);
+        assert_eq!(wrapped.subsystem(), "");
+        assert_eq!(wrapped.recording(), wrapped.enabled());
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_event_with_backtrace() {
+        let sink = Arc::new(MemorySink::new());
+        let log = OsLog::dynamic_stack_tracing("test_backtrace").with_sink(sink.clone());
+        assert_eq!(
+            log.stack_tracing_logger().as_raw_handle(),
+            log.as_raw_handle()
+        );
+        let id = SignpostId::next();
+        log.event_with_backtrace(id, "event");
+        let records = sink.take();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, id);
+
+        // Other categories emit on the stack tracing logger of the subsystem, to the same sink.
+        let log = OsLog::points_of_interest("test_backtrace")
+            .with_sink(sink.clone())
+            .with_max_name_len(4);
+        let stack_tracing = log.stack_tracing_logger();
+        assert_eq!(stack_tracing.subsystem(), "test_backtrace");
+        assert_eq!(stack_tracing.category(), categories::DYNAMIC_STACK_TRACING);
+        assert_eq!(stack_tracing.max_name_len(), 4);
+        assert_eq!(
+            stack_tracing.as_raw_handle(),
+            log.stack_tracing_logger().as_raw_handle()
+        );
+        let id = SignpostId::next();
+        log.event_with_backtrace(id, "event");
+        let records = sink.take();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, id);
+        assert_eq!(records[0].name, "e…");
+        assert_eq!(records[0].kind, SignpostKind::Event);
+    }
+
+    #[test]
+    #[cfg(all(feature = "debug_checks", debug_assertions, not(feature = "disabled")))]
+    fn test_debug_checks() {
+        let log =
+            OsLog::points_of_interest("test_debug_checks").with_sink(Arc::new(MemorySink::new()));
+        let id = SignpostId::next();
+
+        // Reusing an ID and name after the interval ended, or for another name, is fine.
+        log.interval(id, "interval").end();
+        let _interval = log.interval(id, "interval");
+        let _other = log.interval(id, "other");
+
+        let overlapping = std::panic::catch_unwind(|| {
+            let _overlapping = log.interval(id, "interval");
+        });
+        assert!(overlapping.is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interval_with_begin_event() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::next();
+        let interval = log.interval_with_begin_event(id, "interval");
+
+        assert_eq!(interval.id(), id);
+        assert_eq!(interval.name, "interval");
+        assert!(!interval.ended);
+        drop(interval);
+        // The event marks the start of the interval in the events lane.
+        expect_records(
+            &sink,
+            &[
+                (id, "interval", None, SignpostKind::Event),
+                (id, "interval", None, SignpostKind::IntervalBegin),
+                (id, "interval", None, SignpostKind::IntervalEnd),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interval_panic() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::next();
+        let result = std::panic::catch_unwind(|| {
+            let _interval = log.interval(id, "interval");
+            panic!("failed");
+        });
+        assert!(result.is_err());
+
+        // Ending the interval while unwinding doesn't abort, and later intervals still work.
+        let interval = log.interval(id, "interval");
+        interval.end();
+        expect_records(
+            &sink,
+            &[
+                (id, "interval", None, SignpostKind::IntervalBegin),
+                (id, "interval", Some("panicked"), SignpostKind::IntervalEnd),
+                (id, "interval", None, SignpostKind::IntervalBegin),
+                (id, "interval", None, SignpostKind::IntervalEnd),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_owned_interval() {
+        struct Connection {
+            interval: OwnedSignpostInterval,
+        }
+
+        let (log, sink) = memory_log();
+        let connection = {
+            let log = Arc::new(log);
+            let id = SignpostId::generate(&log);
+            Connection {
+                interval: log.interval_owned_with_message(id, "connection", "open"),
+            }
+        };
+
+        // The interval keeps the log alive after the `Arc` above was dropped.
+        let id = connection.interval.id();
+        assert_eq!(connection.interval.log.subsystem(), "test_memory");
+        connection.interval.event("request");
+        connection.interval.end();
+        expect_records(
+            &sink,
+            &[
+                (id, "connection", Some("open"), SignpostKind::IntervalBegin),
+                (id, "request", None, SignpostKind::Event),
+                (id, "connection", None, SignpostKind::IntervalEnd),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interval_into_owned() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::next();
+
+        let interval = log.interval(id, "detached").into_owned();
+        assert!(Arc::ptr_eq(&interval.log.inner, &log.inner));
+        std::thread::spawn(move || {
+            assert_eq!(interval.id(), id);
+            interval.end();
         })
-        .expect_err("Should panic when configuring twice");
+        .join()
+        .unwrap();
+
+        // Begun and ended exactly once.
+        expect_records(
+            &sink,
+            &[
+                (id, "detached", None, SignpostKind::IntervalBegin),
+                (id, "detached", None, SignpostKind::IntervalEnd),
+            ],
+        );
     }
 
     #[test]
-    fn test_bindgen_integration() {
-        // Test that os_log_t is a pointer type from generated bindings
-        let null_log: sys::os_log_t = std::ptr::null_mut();
-        assert!(null_log.is_null());
+    fn test_interval_elapsed() {
+        let log = OsLog::new("test_elapsed".to_string(), categories::POINTS_OF_INTEREST);
+        let mut interval = log.interval(SignpostId::generate(&log), "interval");
+        assert_eq!(interval.started_at.is_some(), log.enabled());
 
-        // Test that os_signpost_id_t is u64 from generated bindings
-        let id: sys::os_signpost_id_t = 42;
-        assert_eq!(id, 42u64);
+        interval.started_at = Some(Instant::now() - Duration::from_millis(10));
+        assert!(interval.elapsed() >= Duration::from_millis(10));
+        assert!(interval.end() >= Duration::from_millis(10));
+    }
 
-        // Test that os_signpost_type_t is u8 from generated bindings
-        let signpost_type: sys::os_signpost_type_t = sys::SIGNPOST_TYPE_EVENT;
-        assert_eq!(signpost_type, 0u8);
+    #[test]
+    fn test_signpost_scope() {
+        let _guard = Signpost::configure_for_test("test_scope", categories::POINTS_OF_INTEREST);
 
-        // Verify generated constants have expected values (matching Apple's headers)
-        assert_eq!(sys::SIGNPOST_TYPE_EVENT, 0);
-        assert_eq!(sys::SIGNPOST_TYPE_INTERVAL_BEGIN, 1);
-        assert_eq!(sys::SIGNPOST_TYPE_INTERVAL_END, 2);
+        fn parse(input: &str) -> Result<u32, std::num::ParseIntError> {
+            let value = signpost_scope!("parse", { input.parse::<u32>()? });
+            signpost_scope_with_message!("check", input, {
+                if value == 0 {
+                    return Ok(0);
+                }
+            });
+            Ok(value * 2)
+        }
+
+        assert_eq!(signpost_scope!("value", { 40 + 2 }), 42);
+        assert_eq!(parse("21"), Ok(42));
+        assert_eq!(parse("0"), Ok(0));
+        assert!(parse("invalid").is_err());
     }
 
     #[test]
-    fn test_error_types() {
-        let error = SignpostError::NotConfigured;
-        let error_msg = format!("{}", error);
-        assert!(error_msg.contains("initialized"));
+    fn test_signpost_name() {
+        let name = signpost_name!("name");
+        if cfg!(feature = "source_location") {
+            let location = format!(" ({}:{})", file!(), line!() - 2);
+            assert_eq!(name, format!("{}::name{location}", function_name!()));
+        } else {
+            assert_eq!(name, "signpost::tests::test_signpost_name::name");
+        }
+    }
 
-        let error = SignpostError::InvalidScope;
-        assert_eq!(format!("{}", error), "Invalid scope for operation");
+    #[test]
+    fn test_interior_nul() {
+        assert_eq!(to_cstring("name").as_bytes(), b"name");
+        assert_eq!(to_cstring("a\0b\0").to_str(), Ok("a\u{FFFD}b\u{FFFD}"));
+    }
 
-        let error = SignpostError::InvalidId;
-        assert_eq!(format!("{}", error), "Invalid signpost ID");
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interior_nul_emission() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::generate(&log);
+        log.event_with_message(id, "event\0name", "message\0with nul");
+        expect_records(
+            &sink,
+            &[(
+                id,
+                "event\u{FFFD}name",
+                Some("message\u{FFFD}with nul"),
+                SignpostKind::Event,
+            )],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interval_if() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::next();
+
+        assert!(log.interval_if(false, id, "skipped").is_none());
+        log.event_if(false, id, "skipped");
+        expect_records(&sink, &[]);
+
+        let interval = log.interval_if(true, id, "interval");
+        assert_eq!(interval.as_ref().map(SignpostInterval::id), Some(id));
+        log.event_if(true, id, "event");
+        drop(interval);
+        expect_records(
+            &sink,
+            &[
+                (id, "interval", None, SignpostKind::IntervalBegin),
+                (id, "event", None, SignpostKind::Event),
+                (id, "interval", None, SignpostKind::IntervalEnd),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interval_begin_end() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::next();
+
+        interval_begin(&log, id, "manual");
+        interval_end(&log, id, "manual");
+
+        expect_records(
+            &sink,
+            &[
+                (id, "manual", None, SignpostKind::IntervalBegin),
+                (id, "manual", None, SignpostKind::IntervalEnd),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_interval_group() {
+        let (log, sink) = memory_log();
+
+        let mut group = log
+            .group()
+            .interval(SignpostId::next(), "first")
+            .interval(SignpostId::next(), "second");
+        group.push(SignpostId::next(), "third");
+        let ids = group.ids();
+        assert_eq!(ids.len(), 3);
+        group.end();
+
+        expect_records(
+            &sink,
+            &[
+                (ids[0], "first", None, SignpostKind::IntervalBegin),
+                (ids[1], "second", None, SignpostKind::IntervalBegin),
+                (ids[2], "third", None, SignpostKind::IntervalBegin),
+                (ids[2], "third", None, SignpostKind::IntervalEnd),
+                (ids[1], "second", None, SignpostKind::IntervalEnd),
+                (ids[0], "first", None, SignpostKind::IntervalEnd),
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_measure() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::next();
+        log.measure(id, "queue depth", 12.5);
+
+        expect_records(
+            &sink,
+            &[(id, "queue depth", Some("12.5"), SignpostKind::Event)],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_flush() {
+        #[derive(Debug, Default)]
+        struct BufferedSink {
+            buffered: AtomicU64,
+            flushed: AtomicU64,
+        }
+
+        impl SignpostSink for BufferedSink {
+            fn enabled(&self, _log: &OsLog) -> bool {
+                true
+            }
+
+            fn emit(&self, _log: &OsLog, _emission: &Emission<'_>) {
+                self.buffered.fetch_add(1, Ordering::Relaxed);
+            }
+
+            fn flush(&self, _log: &OsLog) {
+                let buffered = self.buffered.swap(0, Ordering::Relaxed);
+                self.flushed.fetch_add(buffered, Ordering::Relaxed);
+            }
+        }
+
+        let sink = Arc::new(BufferedSink::default());
+        let log = OsLog::points_of_interest("test_flush").with_sink(sink.clone());
+        log.event(SignpostId::next(), "event");
+        log.flush();
+        assert_eq!(sink.flushed.load(Ordering::Relaxed), 2);
+
+        let (log, sink) = memory_log();
+        log.flush();
+        let records = sink.take();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].name, FLUSH_EVENT);
+        assert_eq!(records[0].kind, SignpostKind::Event);
+    }
+
+    #[test]
+    fn test_truncate_name() {
+        assert_eq!(truncate_name("name", 4), "name");
+        assert_eq!(truncate_name("long name", 7), "long\u{2026}");
+        // Cut before the multi-byte character instead of inside it.
+        assert_eq!(truncate_name("a\u{e9}bcd", 5), "a\u{2026}");
+        assert_eq!(truncate_name("name", 2), "na");
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_max_name_len() {
+        let sink = Arc::new(MemorySink::new());
+        let log = OsLog::builder("test_truncate")
+            .max_name_len(7)
+            .build()
+            .unwrap()
+            .with_sink(sink.clone());
+        assert_eq!(log.max_name_len(), 7);
+        let id = SignpostId::next();
+        drop(log.interval(id, "long name"));
+        log.emit_raw(id, c"raw long name", None, SignpostKind::Event);
+
+        expect_records(
+            &sink,
+            &[
+                (id, "long\u{2026}", None, SignpostKind::IntervalBegin),
+                (id, "long\u{2026}", None, SignpostKind::IntervalEnd),
+                (id, "raw \u{2026}", None, SignpostKind::Event),
+            ],
+        );
     }
 
     #[test]
     fn test_event_functions() {
-        // Try to configure, but ignore if already configured
-        let _ = std::panic::catch_unwind(|| {
-            Signpost::configure("test_events", categories::POINTS_OF_INTEREST);
-        });
+        let _guard = Signpost::configure_for_test("test_events", categories::POINTS_OF_INTEREST);
 
         // Test that event functions compile and execute without panicking
         event!("Test Event");
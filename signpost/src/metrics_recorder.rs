@@ -0,0 +1,249 @@
+//! Recorder for the `metrics` facade that mirrors metric updates as signpost events.
+//!
+//! Provides a [`MetricsRecorder`] that wraps another `metrics::Recorder`, e.g. an exporter,
+//! and additionally emits a measurement event for every update of a bridged metric, so
+//! metrics can be correlated with intervals in Instruments.
+
+use crate::{global_logger, OsLog, SignpostId};
+use metrics::{
+    Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+};
+use std::sync::Arc;
+
+/// Predicate selecting the metrics that are bridged, by name.
+type Filter = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// A `metrics::Recorder` that forwards to an inner recorder and emits signpost events.
+///
+/// Every update of a bridged metric is passed to the inner recorder and emitted with
+/// [`OsLog::measure`], named after the metric, with the value of the update:
+/// - Counters emit the increment, or the new value when set with `absolute`.
+/// - Gauges emit the new value when set, and the increment otherwise, negated for
+///   decrements.
+/// - Histograms emit every recorded value.
+///
+/// Labels are not part of the event name. All metrics are bridged by default, see
+/// [`MetricsRecorderBuilder::filter`].
+///
+/// # Examples
+/// ```ignore
+/// use signpost::MetricsRecorder;
+///
+/// let recorder = MetricsRecorder::builder(PrometheusBuilder::new().build_recorder())
+///     .filter(|name| name.starts_with("queue."))
+///     .build();
+/// metrics::set_global_recorder(recorder).unwrap();
+/// ```
+pub struct MetricsRecorder<R> {
+    inner: R,
+    log: Option<&'static OsLog>,
+    filter: Option<Filter>,
+}
+
+impl<R: Recorder> MetricsRecorder<R> {
+    /// Wrap `inner`, bridging all metrics to the global logger.
+    pub fn new(inner: R) -> Self {
+        Self::builder(inner).build()
+    }
+
+    /// Create a builder to configure the recorder wrapping `inner`.
+    pub fn builder(inner: R) -> MetricsRecorderBuilder<R> {
+        MetricsRecorderBuilder {
+            inner,
+            log: None,
+            filter: None,
+        }
+    }
+
+    /// The wrapped recorder.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// The handle emitting updates of the metric `key`, if it is bridged.
+    fn bridge(&self, key: &Key) -> Option<Bridge> {
+        if !self.filter.as_ref().is_none_or(|filter| filter(key.name())) {
+            return None;
+        }
+        Some(Bridge {
+            log: self.log,
+            name: key.name().to_string(),
+        })
+    }
+}
+
+impl<R: Recorder> Recorder for MetricsRecorder<R> {
+    fn describe_counter(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        self.inner.describe_counter(key, unit, description);
+    }
+
+    fn describe_gauge(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        self.inner.describe_gauge(key, unit, description);
+    }
+
+    fn describe_histogram(&self, key: KeyName, unit: Option<Unit>, description: SharedString) {
+        self.inner.describe_histogram(key, unit, description);
+    }
+
+    fn register_counter(&self, key: &Key, metadata: &Metadata<'_>) -> Counter {
+        let inner = self.inner.register_counter(key, metadata);
+        match self.bridge(key) {
+            Some(bridge) => Counter::from_arc(Arc::new(Bridged { inner, bridge })),
+            None => inner,
+        }
+    }
+
+    fn register_gauge(&self, key: &Key, metadata: &Metadata<'_>) -> Gauge {
+        let inner = self.inner.register_gauge(key, metadata);
+        match self.bridge(key) {
+            Some(bridge) => Gauge::from_arc(Arc::new(Bridged { inner, bridge })),
+            None => inner,
+        }
+    }
+
+    fn register_histogram(&self, key: &Key, metadata: &Metadata<'_>) -> Histogram {
+        let inner = self.inner.register_histogram(key, metadata);
+        match self.bridge(key) {
+            Some(bridge) => Histogram::from_arc(Arc::new(Bridged { inner, bridge })),
+            None => inner,
+        }
+    }
+}
+
+/// Builder for a [`MetricsRecorder`].
+pub struct MetricsRecorderBuilder<R> {
+    inner: R,
+    log: Option<&'static OsLog>,
+    filter: Option<Filter>,
+}
+
+impl<R: Recorder> MetricsRecorderBuilder<R> {
+    /// Emit the events to `log`, the global logger by default.
+    pub fn log(mut self, log: &'static OsLog) -> Self {
+        self.log = Some(log);
+        self
+    }
+
+    /// Only bridge metrics whose name matches `filter`, all metrics by default.
+    ///
+    /// The filter is evaluated once per metric when it is registered, so it doesn't add to
+    /// the cost of updates.
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Build the recorder.
+    pub fn build(self) -> MetricsRecorder<R> {
+        MetricsRecorder {
+            inner: self.inner,
+            log: self.log,
+            filter: self.filter,
+        }
+    }
+}
+
+/// Emits the updates of a bridged metric.
+struct Bridge {
+    log: Option<&'static OsLog>,
+    name: String,
+}
+
+impl Bridge {
+    fn measure(&self, value: f64) {
+        let log = self.log.unwrap_or_else(global_logger);
+        log.measure(SignpostId::next(), &self.name, value);
+    }
+}
+
+/// A metric handle of the inner recorder that also emits its updates.
+struct Bridged<H> {
+    inner: H,
+    bridge: Bridge,
+}
+
+impl CounterFn for Bridged<Counter> {
+    fn increment(&self, value: u64) {
+        self.inner.increment(value);
+        self.bridge.measure(value as f64);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.inner.absolute(value);
+        self.bridge.measure(value as f64);
+    }
+}
+
+impl GaugeFn for Bridged<Gauge> {
+    fn increment(&self, value: f64) {
+        self.inner.increment(value);
+        self.bridge.measure(value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.inner.decrement(value);
+        self.bridge.measure(-value);
+    }
+
+    fn set(&self, value: f64) {
+        self.inner.set(value);
+        self.bridge.measure(value);
+    }
+}
+
+impl HistogramFn for Bridged<Histogram> {
+    fn record(&self, value: f64) {
+        self.inner.record(value);
+        self.bridge.measure(value);
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "disabled"))]
+mod tests {
+    use super::*;
+    use crate::tests::memory_log;
+    use crate::SignpostKind;
+    use metrics::NoopRecorder;
+
+    #[test]
+    fn test_metrics_recorder() {
+        let (log, sink) = memory_log();
+        let log: &'static OsLog = Box::leak(Box::new(log));
+        let recorder = MetricsRecorder::builder(NoopRecorder)
+            .log(log)
+            .filter(|name| name.starts_with("queue."))
+            .build();
+
+        metrics::with_local_recorder(&recorder, || {
+            metrics::counter!("queue.pushed").increment(2);
+            metrics::counter!("requests").increment(1);
+            metrics::gauge!("queue.depth").set(5.0);
+            metrics::gauge!("queue.depth").decrement(1.5);
+            metrics::histogram!("queue.wait", "shard" => "1").record(0.25);
+        });
+
+        let events: Vec<_> = sink
+            .take()
+            .into_iter()
+            .map(|record| {
+                assert_eq!(record.kind, SignpostKind::Event);
+                (record.name, record.message.unwrap())
+            })
+            .collect();
+        assert_eq!(
+            events,
+            [
+                ("queue.pushed", "2"),
+                ("queue.depth", "5"),
+                ("queue.depth", "-1.5"),
+                ("queue.wait", "0.25"),
+            ]
+            .map(|(name, message)| (name.to_string(), message.to_string()))
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Signpost intervals around dropping a value.
+//!
+//! Provides [`SignpostOnDrop`] to measure how long dropping a value takes, e.g. a large
+//! resource whose `Drop` isn't a function that can be annotated with `#[signpost]`.
+
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+
+use crate::{current_logger, OsLog, SignpostId};
+
+/// A wrapper that measures dropping its value with a signpost interval.
+///
+/// The interval begins when the wrapper is dropped and ends once the value is dropped.
+/// The wrapper dereferences to the value. Create it with [`signpost_drop!`](crate::signpost_drop)
+/// to name the interval after the calling function.
+///
+/// # Examples
+/// ```ignore
+/// use signpost::SignpostOnDrop;
+///
+/// let cache = SignpostOnDrop::new(build_cache(), "drop cache");
+/// cache.lookup(key);
+/// drop(cache); // measured
+/// ```
+pub struct SignpostOnDrop<T> {
+    value: ManuallyDrop<T>,
+    logger: &'static OsLog,
+    name: String,
+}
+
+impl<T> SignpostOnDrop<T> {
+    /// Wrap `value`, measuring its drop on the current logger.
+    pub fn new<N: Into<String>>(value: T, name: N) -> Self {
+        Self::with_logger(value, current_logger(), name)
+    }
+
+    /// Wrap `value`, measuring its drop on the given logger.
+    pub fn with_logger<N: Into<String>>(value: T, logger: &'static OsLog, name: N) -> Self {
+        Self {
+            value: ManuallyDrop::new(value),
+            logger,
+            name: name.into(),
+        }
+    }
+
+    /// Unwrap the value without measuring its drop.
+    pub fn into_inner(mut self) -> T {
+        // SAFETY: the value is taken once, and the wrapper's `Drop` doesn't run.
+        let value = unsafe { ManuallyDrop::take(&mut self.value) };
+        drop(std::mem::take(&mut self.name));
+        std::mem::forget(self);
+        value
+    }
+}
+
+impl<T> Deref for SignpostOnDrop<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for SignpostOnDrop<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T> Drop for SignpostOnDrop<T> {
+    fn drop(&mut self) {
+        let _interval = self.logger.interval(SignpostId::next(), &self.name);
+        // SAFETY: the value is dropped once, and never accessed afterwards.
+        unsafe { ManuallyDrop::drop(&mut self.value) };
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "disabled"))]
+mod tests {
+    use super::*;
+    use crate::tests::{expect_records, memory_log};
+    use crate::SignpostKind;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Element;
+
+    impl Drop for Element {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_signpost_on_drop() {
+        let (log, sink) = memory_log();
+        let log: &'static OsLog = Box::leak(Box::new(log));
+
+        let elements = SignpostOnDrop::with_logger(vec![Element, Element, Element], log, "drop");
+        assert_eq!(elements.len(), 3);
+        drop(elements);
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 3);
+
+        let id = sink.records()[0].id;
+        expect_records(
+            &sink,
+            &[
+                (id, "drop", None, SignpostKind::IntervalBegin),
+                (id, "drop", None, SignpostKind::IntervalEnd),
+            ],
+        );
+
+        // Unwrapped values are dropped without an interval.
+        let element = SignpostOnDrop::with_logger(Element, log, "drop").into_inner();
+        drop(element);
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 4);
+        assert!(sink.take().is_empty());
+
+        let wrapped = crate::signpost_drop!(vec![1, 2]);
+        assert_eq!(*wrapped, [1, 2]);
+    }
+}
@@ -0,0 +1,333 @@
+//! Destinations for emitted signposts.
+//!
+//! Every [`OsLog`] emits through a [`SignpostSink`], by default the [`OsSink`] which hands
+//! signposts to os_signpost. With the `test-util` feature, a `MemorySink` records them
+//! instead, so tests can assert which signposts were emitted without Instruments. With the
+//! `console` feature, the default is a `ConsoleSink` also printing signposts to stderr.
+
+use std::ffi::CStr;
+use std::panic::RefUnwindSafe;
+
+use crate::args::render;
+use crate::{sys, to_cstring, FormatBuffer, OsLog, SignpostArg, SignpostId, SignpostKind};
+
+#[cfg(feature = "console")]
+use std::sync::atomic::AtomicBool as ConsoleFlag;
+
+#[cfg(any(test, feature = "test-util"))]
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex, PoisonError,
+};
+
+/// A signpost on its way to a [`SignpostSink`].
+#[derive(Debug, Clone, Copy)]
+pub struct Emission<'a> {
+    /// The signpost ID.
+    pub id: SignpostId,
+    /// The signpost name.
+    pub name: &'a str,
+    /// The format string of the message, if any.
+    pub format: Option<&'a CStr>,
+    /// The arguments for the conversion specifiers of `format`.
+    pub args: &'a [SignpostArg<'a>],
+    /// The kind of signpost.
+    pub kind: SignpostKind,
+}
+
+impl Emission<'_> {
+    /// The message with the arguments substituted into the format string.
+    pub fn message(&self) -> Option<String> {
+        self.format.map(|format| render(format, self.args))
+    }
+}
+
+/// Destination of the signposts emitted through an [`OsLog`], see [`OsLog::with_sink`].
+///
+/// Sinks are shared by clones of a log across threads, and must be unwind safe like the
+/// rest of `OsLog`, so intervals can end while unwinding.
+pub trait SignpostSink: Send + Sync + RefUnwindSafe + std::fmt::Debug {
+    /// Whether signposts of `log` are recorded, see [`OsLog::enabled`].
+    fn enabled(&self, log: &OsLog) -> bool;
+
+    /// Record a signpost of `log`. Only called while the sink is enabled.
+    fn emit(&self, log: &OsLog, emission: &Emission<'_>);
+
+    /// Commit the signposts of `log` recorded so far, see [`OsLog::flush`].
+    ///
+    /// Sinks that record synchronously, like os_signpost itself, have nothing to do.
+    fn flush(&self, _log: &OsLog) {}
+}
+
+/// The default sink, emitting signposts through os_signpost.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsSink;
+
+impl SignpostSink for OsSink {
+    fn enabled(&self, log: &OsLog) -> bool {
+        unsafe { sys::os_signpost_enabled(log.get()) }
+    }
+
+    fn emit(&self, log: &OsLog, emission: &Emission<'_>) {
+        log.emit_encoded(
+            emission.id,
+            &to_cstring(emission.name),
+            emission.format,
+            FormatBuffer::encode(emission.format, emission.args),
+            emission.kind,
+        );
+    }
+}
+
+/// Whether the `ConsoleSink` prints signposts, see `ConsoleSink::set_enabled`.
+#[cfg(feature = "console")]
+static CONSOLE: ConsoleFlag = ConsoleFlag::new(true);
+
+/// The default sink with the `console` feature, printing signposts to stderr on top of
+/// emitting them through os_signpost.
+///
+/// Shows signpost activity where Instruments isn't available, e.g. on CI or over SSH, and
+/// on non-Apple targets, where it is the only output. Lines are terse, with the time since
+/// the first signpost in seconds:
+///
+/// ```text
+/// [signpost    0.000012] BEGIN my_crate::load (id=0x1)
+/// [signpost    0.004211] END my_crate::load (id=0x1): rows=3
+/// ```
+#[cfg(feature = "console")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleSink;
+
+#[cfg(feature = "console")]
+impl ConsoleSink {
+    /// Turn printing to stderr on or off at runtime, on by default.
+    ///
+    /// While off, signposts are only emitted through os_signpost.
+    pub fn set_enabled(enabled: bool) {
+        CONSOLE.store(enabled, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Whether signposts are printed to stderr.
+    pub fn is_enabled() -> bool {
+        CONSOLE.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "console")]
+impl SignpostSink for ConsoleSink {
+    fn enabled(&self, log: &OsLog) -> bool {
+        Self::is_enabled() || OsSink.enabled(log)
+    }
+
+    fn emit(&self, log: &OsLog, emission: &Emission<'_>) {
+        if Self::is_enabled() {
+            eprintln!("{}", console_line(crate::monotonic_nanos(), emission));
+        }
+        if OsSink.enabled(log) {
+            OsSink.emit(log, emission);
+        }
+    }
+}
+
+/// Formats a signpost emitted `nanos` after the first one for the console.
+#[cfg(feature = "console")]
+fn console_line(nanos: u64, emission: &Emission<'_>) -> String {
+    let kind = match emission.kind {
+        SignpostKind::Event => "EVENT",
+        SignpostKind::IntervalBegin => "BEGIN",
+        SignpostKind::IntervalEnd => "END",
+    };
+    let mut line = format!(
+        "[signpost {:>4}.{:06}] {kind} {} (id={})",
+        nanos / 1_000_000_000,
+        nanos % 1_000_000_000 / 1_000,
+        emission.name,
+        emission.id,
+    );
+    if let Some(message) = emission.message() {
+        line.push_str(": ");
+        line.push_str(&message);
+    }
+    line
+}
+
+/// A signpost recorded by a `MemorySink`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignpostRecord {
+    /// The signpost ID.
+    pub id: SignpostId,
+    /// The signpost name.
+    pub name: String,
+    /// The message with its arguments substituted, if any.
+    pub message: Option<String>,
+    /// The kind of signpost.
+    pub kind: SignpostKind,
+}
+
+/// A sink that records signposts in memory, for asserting instrumentation in tests.
+///
+/// # Examples
+/// ```ignore
+/// use std::sync::Arc;
+/// use signpost::{MemorySink, OsLog, SignpostId, SignpostKind};
+///
+/// let sink = Arc::new(MemorySink::new());
+/// let log = OsLog::points_of_interest("com.myapp").with_sink(sink.clone());
+///
+/// log.interval(SignpostId::next(), "Load").end();
+/// let kinds: Vec<_> = sink.records().iter().map(|record| record.kind).collect();
+/// assert_eq!(kinds, [SignpostKind::IntervalBegin, SignpostKind::IntervalEnd]);
+/// ```
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Debug)]
+pub struct MemorySink {
+    enabled: AtomicBool,
+    records: Mutex<Vec<SignpostRecord>>,
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl Default for MemorySink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl MemorySink {
+    /// Create an enabled sink without records.
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            records: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Simulate a recording starting or stopping.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// The signposts recorded so far.
+    pub fn records(&self) -> Vec<SignpostRecord> {
+        self.lock().clone()
+    }
+
+    /// Remove and return the signposts recorded so far.
+    pub fn take(&self) -> Vec<SignpostRecord> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<SignpostRecord>> {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl SignpostSink for MemorySink {
+    fn enabled(&self, _log: &OsLog) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    fn emit(&self, _log: &OsLog, emission: &Emission<'_>) {
+        self.lock().push(SignpostRecord {
+            id: emission.id,
+            name: emission.name.to_string(),
+            message: emission.message(),
+            kind: emission.kind,
+        });
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "console")]
+mod console_tests {
+    use super::*;
+
+    #[test]
+    fn test_console_line() {
+        let emission = Emission {
+            id: SignpostId::from_raw(0x2a).unwrap(),
+            name: "my_crate::load",
+            format: None,
+            args: &[],
+            kind: SignpostKind::IntervalBegin,
+        };
+        assert_eq!(
+            console_line(1_234_567_890, &emission),
+            "[signpost    1.234567] BEGIN my_crate::load (id=0x2a)"
+        );
+
+        let args = [SignpostArg::U64(3)];
+        let emission = Emission {
+            format: Some(c"rows=%{public}llu"),
+            args: &args,
+            kind: SignpostKind::IntervalEnd,
+            ..emission
+        };
+        assert_eq!(
+            console_line(12_000, &emission),
+            "[signpost    0.000012] END my_crate::load (id=0x2a): rows=3"
+        );
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "disabled"))]
+mod tests {
+    use super::*;
+    use crate::tests::{expect_records, memory_log};
+
+    #[test]
+    fn test_memory_sink() {
+        let (log, sink) = memory_log();
+        let id = SignpostId::next();
+
+        let interval = log.interval_with_message(id, "interval", "start");
+        interval.event("checkpoint");
+        log.event_with_args(
+            id,
+            "args",
+            c"%{public}llu items in %{public}s, 100%%",
+            &[SignpostArg::U64(3), SignpostArg::Str("batch")],
+        );
+        interval.end_with_message("done");
+        log.emit_raw(id, c"raw", Some(c"message"), SignpostKind::Event);
+
+        expect_records(
+            &sink,
+            &[
+                (id, "interval", Some("start"), SignpostKind::IntervalBegin),
+                (id, "checkpoint", None, SignpostKind::Event),
+                (
+                    id,
+                    "args",
+                    Some("3 items in batch, 100%"),
+                    SignpostKind::Event,
+                ),
+                (id, "interval", Some("done"), SignpostKind::IntervalEnd),
+                (id, "raw", Some("message"), SignpostKind::Event),
+            ],
+        );
+        assert!(sink.records().is_empty());
+    }
+
+    #[test]
+    fn test_memory_sink_disabled() {
+        let (log, sink) = memory_log();
+        assert!(log.enabled());
+
+        sink.set_enabled(false);
+        assert!(!log.enabled());
+        let interval = log.interval(SignpostId::next(), "interval");
+        log.event(SignpostId::next(), "event");
+
+        // Recording starts mid-interval, so the end would be an orphan.
+        sink.set_enabled(true);
+        drop(interval);
+        assert!(sink.records().is_empty());
+
+        // Clones share the sink.
+        log.clone().event(SignpostId::next(), "event");
+        assert_eq!(sink.records().len(), 1);
+    }
+}
@@ -0,0 +1,175 @@
+//! Fan-out of signposts to several loggers.
+//!
+//! Provides [`TeeLog`] to emit the same signposts under several categories, e.g.
+//! [`categories::POINTS_OF_INTEREST`](crate::categories::POINTS_OF_INTEREST) for
+//! Instruments and a custom category read by a dashboard.
+
+use crate::{OsLog, SignpostId, SignpostInterval};
+
+/// A log that emits every signpost to each of several loggers.
+///
+/// Each logger checks its own enabled state, so disabled loggers are skipped.
+///
+/// # Examples
+/// ```ignore
+/// use signpost::{categories, OsLog, SignpostId, TeeLog};
+///
+/// let log = TeeLog::new([
+///     OsLog::points_of_interest("com.myapp"),
+///     OsLog::new("com.myapp".to_string(), categories::custom!("Dashboard")),
+/// ]);
+/// let _interval = log.interval(SignpostId::next(), "Sync");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TeeLog {
+    logs: Vec<OsLog>,
+}
+
+impl TeeLog {
+    /// Create a log emitting to each of `logs`.
+    pub fn new<I: IntoIterator<Item = OsLog>>(logs: I) -> Self {
+        Self {
+            logs: logs.into_iter().collect(),
+        }
+    }
+
+    /// The loggers signposts are emitted to.
+    pub fn logs(&self) -> &[OsLog] {
+        &self.logs
+    }
+
+    /// Check if signpost logging is enabled for any of the loggers.
+    pub fn enabled(&self) -> bool {
+        self.logs.iter().any(OsLog::enabled)
+    }
+
+    /// Emit a simple event on every logger.
+    pub fn event<T: AsRef<str>>(&self, id: SignpostId, name: T) {
+        for log in &self.logs {
+            log.event(id, name.as_ref());
+        }
+    }
+
+    /// Emit an event with a message on every logger.
+    pub fn event_with_message<T1: AsRef<str>, T2: AsRef<str>>(
+        &self,
+        id: SignpostId,
+        name: T1,
+        message: T2,
+    ) {
+        for log in &self.logs {
+            log.event_with_message(id, name.as_ref(), message.as_ref());
+        }
+    }
+
+    /// Start a signpost interval on every logger.
+    pub fn interval<T: AsRef<str>>(&self, id: SignpostId, name: T) -> TeeInterval<'_> {
+        TeeInterval {
+            intervals: self
+                .logs
+                .iter()
+                .map(|log| log.interval(id, name.as_ref()))
+                .collect(),
+        }
+    }
+
+    /// Start a signpost interval with a message on every logger.
+    pub fn interval_with_message<T1: AsRef<str>, T2: AsRef<str>>(
+        &self,
+        id: SignpostId,
+        name: T1,
+        message: T2,
+    ) -> TeeInterval<'_> {
+        TeeInterval {
+            intervals: self
+                .logs
+                .iter()
+                .map(|log| log.interval_with_message(id, name.as_ref(), message.as_ref()))
+                .collect(),
+        }
+    }
+}
+
+/// A signpost interval open on each logger of a [`TeeLog`].
+///
+/// The interval ends on all loggers when it is dropped or ended explicitly.
+pub struct TeeInterval<'a> {
+    intervals: Vec<SignpostInterval<'a>>,
+}
+
+impl TeeInterval<'_> {
+    /// The signpost ID of the interval, shared by all loggers.
+    pub fn id(&self) -> Option<SignpostId> {
+        self.intervals.first().map(SignpostInterval::id)
+    }
+
+    /// Emit an event within the interval on every logger.
+    pub fn event<T: AsRef<str>>(&self, name: T) {
+        for interval in &self.intervals {
+            interval.event(name.as_ref());
+        }
+    }
+
+    /// End the interval on every logger immediately instead of when it is dropped.
+    pub fn end(self) {
+        for interval in self.intervals {
+            interval.end();
+        }
+    }
+
+    /// End the interval on every logger, attaching a message to the end signposts.
+    pub fn end_with_message<T: AsRef<str>>(self, message: T) {
+        for interval in self.intervals {
+            interval.end_with_message(message.as_ref());
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "disabled"))]
+mod tests {
+    use super::*;
+    use crate::tests::expect_records;
+    use crate::{categories, MemorySink, SignpostKind};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_tee_log() {
+        let sinks: [Arc<MemorySink>; 3] = Default::default();
+        sinks[2].set_enabled(false);
+        let log = TeeLog::new([
+            OsLog::points_of_interest("test_tee").with_sink(sinks[0].clone()),
+            OsLog::dynamic_tracing("test_tee").with_sink(sinks[1].clone()),
+            OsLog::points_of_interest("test_tee").with_sink(sinks[2].clone()),
+        ]);
+        assert_eq!(log.logs().len(), 3);
+        assert!(log.enabled());
+
+        let id = SignpostId::next();
+        log.event_with_message(id, "event", "message");
+        let interval = log.interval(id, "interval");
+        assert_eq!(interval.id(), Some(id));
+        assert_eq!(interval.intervals.len(), 3);
+        assert_eq!(
+            interval.intervals[1].log.category(),
+            categories::DYNAMIC_TRACING
+        );
+        interval.event("checkpoint");
+        interval.end_with_message("done");
+
+        for sink in &sinks[..2] {
+            expect_records(
+                sink,
+                &[
+                    (id, "event", Some("message"), SignpostKind::Event),
+                    (id, "interval", None, SignpostKind::IntervalBegin),
+                    (id, "checkpoint", None, SignpostKind::Event),
+                    (id, "interval", Some("done"), SignpostKind::IntervalEnd),
+                ],
+            );
+        }
+        expect_records(&sinks[2], &[]);
+
+        assert_eq!(TeeLog::new([]).interval(id, "empty").id(), None);
+    }
+}
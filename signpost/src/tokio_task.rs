@@ -0,0 +1,100 @@
+//! Task-local loggers for tokio.
+//!
+//! Extends [`Signpost::scoped_subsystem`] to tasks: the logger of a task is consulted by
+//! the signpost macros and `#[signpost]` whenever the task is polled, across `.await`
+//! points and whichever worker thread polls it.
+
+use crate::{subsystem_logger, OsLog, Signpost};
+use std::future::Future;
+use tokio::task::futures::TaskLocalFuture;
+use tokio::task::JoinHandle;
+
+tokio::task_local! {
+    /// Logger of the task, see [`Signpost::task_subsystem`].
+    static TASK_LOGGER: &'static OsLog;
+}
+
+/// The logger of the current task, if it has one.
+pub(crate) fn task_logger() -> Option<&'static OsLog> {
+    TASK_LOGGER.try_with(|logger| *logger).ok()
+}
+
+impl Signpost {
+    /// Wraps `future` so the signpost macros log to `subsystem` while it is polled.
+    ///
+    /// Unlike [`Signpost::scoped_subsystem`], the logger follows the future across
+    /// `.await` points and threads. Scopes of [`Signpost::scoped_subsystem`] entered while
+    /// the future is polled take precedence, and futures nest like scopes. The category is
+    /// chosen like for [`Signpost::scoped_subsystem`].
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let response = Signpost::task_subsystem("com.example.tenant-a", handle(request)).await;
+    /// ```
+    pub fn task_subsystem<F: Future>(
+        subsystem: &str,
+        future: F,
+    ) -> TaskLocalFuture<&'static OsLog, F> {
+        TASK_LOGGER.scope(subsystem_logger(subsystem), future)
+    }
+
+    /// Spawns `future` on the current tokio runtime as a task logging to `subsystem`.
+    ///
+    /// Shorthand for `tokio::spawn(Signpost::task_subsystem(subsystem, future))`, see
+    /// [`Signpost::task_subsystem`]. Tasks spawned by the task don't inherit the subsystem,
+    /// so spawn them with this function as well.
+    ///
+    /// # Panics
+    /// When called outside of a tokio runtime, like `tokio::spawn`.
+    pub fn spawn_with_subsystem<F>(subsystem: &str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        tokio::spawn(Self::task_subsystem(subsystem, future))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::current_logger;
+
+    #[test]
+    fn test_task_subsystem() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let global = current_logger().subsystem().to_string();
+
+        let subsystems = runtime.block_on(async {
+            let tenant = Signpost::spawn_with_subsystem("test_task.tenant", async {
+                let before = current_logger().subsystem().to_string();
+                tokio::task::yield_now().await;
+                let after = current_logger().subsystem().to_string();
+                let scoped = Signpost::with_subsystem("test_task.scoped", || {
+                    current_logger().subsystem().to_string()
+                });
+                let nested = Signpost::task_subsystem("test_task.nested", async {
+                    current_logger().subsystem().to_string()
+                })
+                .await;
+                [before, after, scoped, nested]
+            });
+            let other = tokio::spawn(async { current_logger().subsystem().to_string() });
+            (tenant.await.unwrap(), other.await.unwrap())
+        });
+
+        assert_eq!(
+            subsystems.0,
+            [
+                "test_task.tenant",
+                "test_task.tenant",
+                "test_task.scoped",
+                "test_task.nested"
+            ]
+        );
+        assert_eq!(subsystems.1, global);
+        assert!(task_logger().is_none());
+    }
+}
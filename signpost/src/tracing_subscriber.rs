@@ -3,22 +3,99 @@
 //! Provides a [`TracingSubscriber`] that can be used with the `tracing-subscriber`
 //! crate to emit os_signpost intervals and events to be viewed in Apple's Instruments.
 
-use crate::global_logger;
-use crate::{SignpostId, SignpostType};
+use crate::breadcrumbs::{self, Breadcrumb};
+use crate::{fold_reserved, OsLog, SignpostArg, SignpostId, SignpostKind, SignpostScope};
+use crate::{global_logger, CategoryLogger, MESSAGE_FORMAT, PRIVATE_MESSAGE_FORMAT};
 use dashmap::DashMap;
-use tracing::{span, Event, Id, Subscriber};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fmt::Write;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use tracing::{span, Event, Id, Level, Metadata, Subscriber};
 use tracing_subscriber::layer::Context;
 use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::Layer;
 
 struct ActiveInterval {
+    logger: &'static OsLog,
     id: SignpostId,
     name: String,
+    /// Fields recorded after the interval began, emitted with the end signpost.
+    message: Option<String>,
+    /// IDs of the busy intervals of the span, one per nested enter.
+    busy: Vec<SignpostId>,
+    /// The last error recorded by an event within the span, emitted with the end signpost.
+    error: Option<String>,
+    /// Entries naming spans created within the span, one per nested enter.
+    breadcrumbs: Vec<Breadcrumb>,
+}
+
+impl ActiveInterval {
+    /// Name of the intervals measuring the time the span is entered.
+    fn busy_name(&self) -> String {
+        format!("{} (busy)", self.name)
+    }
+
+    /// The message of the end signpost: the recorded fields followed by the last error.
+    fn end_message(&self) -> Option<String> {
+        let error = self.error.as_ref().map(|error| format!("error={error}"));
+        match (&self.message, error) {
+            (Some(message), Some(error)) => Some(format!("{message} {error}")),
+            (message, error) => error.or_else(|| message.clone()),
+        }
+    }
+}
+
+/// Logger for a level mapped to a category, resolved once configured.
+struct LevelLogger {
+    category: &'static CStr,
+    logger: CategoryLogger,
+}
+
+/// How signpost names are derived from tracing metadata.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NameFormat {
+    /// Only the span name, e.g. `worker`.
+    SpanOnly,
+    /// The module path followed by the span name, e.g. `my_crate::component::worker`.
+    #[default]
+    ModuleAndSpan,
+    /// The target followed by the span name, e.g. `my_target::worker`.
+    TargetAndSpan,
+}
+
+impl NameFormat {
+    /// Formats the signpost name for `span_name` recorded with `metadata`.
+    fn format(self, metadata: &Metadata<'_>, span_name: &str) -> String {
+        match self {
+            NameFormat::SpanOnly => span_name.to_string(),
+            NameFormat::ModuleAndSpan => format!(
+                "{}::{}",
+                metadata.module_path().unwrap_or_default(),
+                span_name
+            ),
+            NameFormat::TargetAndSpan => format!("{}::{}", metadata.target(), span_name),
+        }
+    }
 }
 
 /// A tracing subscriber layer that emits signposts for Apple's Instruments
 pub struct TracingSubscriber {
     intervals: DashMap<Id, ActiveInterval>,
+    /// Logger for levels without a category, the global logger if `None`.
+    log: Option<&'static OsLog>,
+    levels: HashMap<Level, LevelLogger>,
+    name_format: NameFormat,
+    /// Target prefixes to emit signposts for, all targets if empty.
+    targets: Vec<String>,
+    public_fields: bool,
+    busy_intervals: bool,
+    breadcrumbs: bool,
+    span_ids: bool,
+    include_ids: bool,
+    poll_events: bool,
+    sample_rate: f64,
 }
 
 impl Default for TracingSubscriber {
@@ -30,8 +107,431 @@ impl Default for TracingSubscriber {
 impl TracingSubscriber {
     /// Create a new signpost tracing subscriber.
     pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    /// Create a builder to configure the signpost tracing subscriber.
+    pub fn builder() -> TracingSubscriberBuilder {
+        TracingSubscriberBuilder::default()
+    }
+
+    /// A snapshot of the spans with an open signpost interval and their signpost names.
+    ///
+    /// Meant as a diagnostic aid for finding intervals that never end, e.g. from a debug
+    /// endpoint. Only clones the span IDs and names.
+    pub fn active_intervals(&self) -> Vec<(Id, String)> {
+        self.intervals
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.name.clone()))
+            .collect()
+    }
+
+    /// The logger for spans and events with the given metadata.
+    ///
+    /// Levels without a configured category use the logger set with
+    /// [`TracingSubscriberBuilder::log`], or the global logger.
+    fn logger(&self, metadata: &Metadata<'_>) -> &'static OsLog {
+        match self.levels.get(metadata.level()) {
+            Some(level) => level.logger.get(level.category),
+            None => self.log.unwrap_or_else(global_logger),
+        }
+    }
+
+    /// The signpost name of an event.
+    ///
+    /// Events are named after the span they belong to, which is the explicit parent or the
+    /// current span for contextual events. Events outside any span use their own name.
+    fn event_name<S>(&self, event: &Event<'_>, ctx: &Context<'_, S>) -> String
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let metadata = event.metadata();
+        match ctx.event_span(event) {
+            Some(span) => self.name_format.format(metadata, span.name()),
+            None => self.name_format.format(metadata, metadata.name()),
+        }
+    }
+
+    /// The format string for span and event messages, depending on their privacy.
+    fn message_format(&self) -> &'static CStr {
+        if self.public_fields {
+            MESSAGE_FORMAT
+        } else {
+            PRIVATE_MESSAGE_FORMAT
+        }
+    }
+
+    /// Emits a signpost with the message formatted according to the configured privacy.
+    fn emit(
+        &self,
+        logger: &OsLog,
+        id: SignpostId,
+        name: &str,
+        message: Option<&str>,
+        signpost_type: SignpostKind,
+    ) {
+        match message {
+            Some(message) => logger.emit_with_args(
+                id,
+                name,
+                Some(self.message_format()),
+                &[SignpostArg::Str(message)],
+                signpost_type,
+            ),
+            None => logger.emit(id, name, None, signpost_type),
+        }
+    }
+
+    /// Emits an event within the interval of the span, returning whether it has one.
+    fn poll_event(&self, id: &Id, message: &str) -> bool {
+        let Some(interval) = self.intervals.get(id) else {
+            return false;
+        };
+        self.emit(
+            interval.logger,
+            interval.id,
+            &interval.name,
+            Some(message),
+            SignpostKind::Event,
+        );
+        true
+    }
+
+    /// Begins a busy interval of the span, returning whether it was begun.
+    fn enter_busy(&self, id: &Id) -> bool {
+        let Some(mut interval) = self.intervals.get_mut(id) else {
+            return false;
+        };
+        let busy_id = SignpostId::next();
+        self.emit(
+            interval.logger,
+            busy_id,
+            &interval.busy_name(),
+            None,
+            SignpostKind::IntervalBegin,
+        );
+        interval.busy.push(busy_id);
+        true
+    }
+
+    /// Ends the innermost busy interval of the span, returning whether one was ended.
+    fn exit_busy(&self, id: &Id) -> bool {
+        let Some(mut interval) = self.intervals.get_mut(id) else {
+            return false;
+        };
+        let Some(busy_id) = interval.busy.pop() else {
+            return false;
+        };
+        self.emit(
+            interval.logger,
+            busy_id,
+            &interval.busy_name(),
+            None,
+            SignpostKind::IntervalEnd,
+        );
+        true
+    }
+
+    /// Names spans created on this thread after the span, returning whether it has an interval.
+    fn enter_breadcrumb(&self, id: &Id) -> bool {
+        let Some(mut interval) = self.intervals.get_mut(id) else {
+            return false;
+        };
+        let breadcrumb = breadcrumbs::push(interval.name.clone());
+        interval.breadcrumbs.push(breadcrumb);
+        true
+    }
+
+    /// Undoes the innermost [`TracingSubscriber::enter_breadcrumb`] of the span.
+    fn exit_breadcrumb(&self, id: &Id) -> bool {
+        let breadcrumb = self
+            .intervals
+            .get_mut(id)
+            .and_then(|mut interval| interval.breadcrumbs.pop());
+        match breadcrumb {
+            Some(breadcrumb) => {
+                breadcrumb.pop();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Records that the interval of `span` follows from the one of `follows`, as
+    /// `follows_from=<hex id>` in its end message. Returns whether both spans have an interval.
+    fn follows_from(&self, span: &Id, follows: &Id) -> bool {
+        // Released before locking the other span, which may live in the same shard.
+        let Some(follows) = self.intervals.get(follows).map(|interval| interval.id) else {
+            return false;
+        };
+        let Some(mut interval) = self.intervals.get_mut(span) else {
+            return false;
+        };
+        let link = format!("follows_from={follows}");
+        match &mut interval.message {
+            Some(message) => {
+                message.push(' ');
+                message.push_str(&link);
+            }
+            None => interval.message = Some(link),
+        }
+        true
+    }
+
+    /// Emits the end signpost of an interval on the log it began on.
+    fn end_interval(&self, interval: ActiveInterval) {
+        for &busy_id in interval.busy.iter().rev() {
+            self.emit(
+                interval.logger,
+                busy_id,
+                &interval.busy_name(),
+                None,
+                SignpostKind::IntervalEnd,
+            );
+        }
+        self.emit(
+            interval.logger,
+            interval.id,
+            &interval.name,
+            interval.end_message().as_deref(),
+            SignpostKind::IntervalEnd,
+        );
+    }
+
+    /// Ends all intervals whose spans haven't closed yet, returning how many were ended.
+    fn end_active_intervals(&self) -> usize {
+        let ids: Vec<Id> = self
+            .intervals
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| self.intervals.remove(&id))
+            .map(|(_, interval)| self.end_interval(interval))
+            .count()
+    }
+
+    /// The signpost ID for the interval of a span, see [`TracingSubscriberBuilder::span_ids`].
+    fn span_signpost_id(&self, id: &Id, logger: &OsLog) -> SignpostId {
+        if self.span_ids && logger.scope() != SignpostScope::System {
+            SignpostId::from_raw(fold_reserved(id.into_u64())).expect("reserved IDs are folded")
+        } else {
+            SignpostId::generate(logger)
+        }
+    }
+
+    /// The message of the begin signpost of a span with the given fields, see
+    /// [`TracingSubscriberBuilder::include_ids`].
+    fn begin_message(&self, fields: Option<String>, id: &Id, parent: Option<Id>) -> Option<String> {
+        if !self.include_ids {
+            return fields;
+        }
+        let mut message = fields.map(|fields| fields + " ").unwrap_or_default();
+        let _ = write!(message, "span={}", id.into_u64());
+        if let Some(parent) = parent {
+            let _ = write!(message, " parent={}", parent.into_u64());
+        }
+        Some(message)
+    }
+
+    /// Decides whether to emit a span or event according to the sample rate.
+    fn sampled(&self) -> bool {
+        self.sample_rate >= 1.0 || random() < self.sample_rate
+    }
+
+    /// Whether spans and events with the given metadata are emitted as signposts.
+    fn matches_target(&self, metadata: &Metadata<'_>) -> bool {
+        let target = metadata.target();
+        self.targets.is_empty()
+            || self.targets.iter().any(|prefix| {
+                target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+    }
+}
+
+/// Builder for a [`TracingSubscriber`].
+///
+/// # Examples
+/// ```ignore
+/// use signpost::{categories, TracingSubscriber};
+/// use tracing::Level;
+///
+/// let subscriber = TracingSubscriber::builder()
+///     .level_category(Level::TRACE, categories::DYNAMIC_TRACING)
+///     .level_category(Level::INFO, categories::POINTS_OF_INTEREST)
+///     .with_targets(["my_crate::hotpath"])
+///     .build();
+/// ```
+pub struct TracingSubscriberBuilder {
+    log: Option<&'static OsLog>,
+    levels: HashMap<Level, &'static CStr>,
+    name_format: NameFormat,
+    targets: Vec<String>,
+    public_fields: bool,
+    busy_intervals: bool,
+    breadcrumbs: bool,
+    span_ids: bool,
+    include_ids: bool,
+    poll_events: bool,
+    sample_rate: f64,
+}
+
+impl Default for TracingSubscriberBuilder {
+    fn default() -> Self {
         Self {
+            log: None,
+            levels: HashMap::new(),
+            name_format: NameFormat::default(),
+            targets: Vec::new(),
+            public_fields: true,
+            busy_intervals: false,
+            breadcrumbs: false,
+            span_ids: false,
+            include_ids: false,
+            poll_events: false,
+            sample_rate: 1.0,
+        }
+    }
+}
+
+impl TracingSubscriberBuilder {
+    /// Emit spans and events to `log`, the global logger by default.
+    ///
+    /// Levels mapped with [`TracingSubscriberBuilder::level_category`] keep using their
+    /// category of the configured subsystem.
+    pub fn log(mut self, log: &'static OsLog) -> Self {
+        self.log = Some(log);
+        self
+    }
+
+    /// Emit spans and events of the given level under `category` of the configured subsystem.
+    pub fn level_category(mut self, level: Level, category: &'static CStr) -> Self {
+        self.levels.insert(level, category);
+        self
+    }
+
+    /// Select how signpost names are derived, [`NameFormat::ModuleAndSpan`] by default.
+    pub fn name_format(mut self, name_format: NameFormat) -> Self {
+        self.name_format = name_format;
+        self
+    }
+
+    /// Only emit spans and events whose target is one of `targets` or a module within it.
+    ///
+    /// Unlike a global filter this only restricts the signposts, other layers still see
+    /// every span and event. All targets are emitted by default.
+    pub fn with_targets<I, T>(mut self, targets: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.targets.extend(targets.into_iter().map(Into::into));
+        self
+    }
+
+    /// Emit span and event fields as public or private os_log data, public by default.
+    ///
+    /// Private data is redacted in logs and traces unless the system is configured to
+    /// reveal it, so disable this for fields that shouldn't show up in a shared trace.
+    pub fn public_fields(mut self, public: bool) -> Self {
+        self.public_fields = public;
+        self
+    }
+
+    /// Emit a nested interval for every time a span is entered, off by default.
+    ///
+    /// The span interval covers the whole lifetime of a span, while the nested intervals
+    /// show when it is actually busy, e.g. while an instrumented future is polled. This
+    /// multiplies the number of signposts.
+    pub fn busy_intervals(mut self, enabled: bool) -> Self {
+        self.busy_intervals = enabled;
+        self
+    }
+
+    /// Name spans after the spans entered when they're created, e.g. `outer > inner`, off by
+    /// default.
+    ///
+    /// The entered spans are tracked per thread, like the intervals of
+    /// [`Signpost::set_breadcrumbs`](crate::Signpost::set_breadcrumbs), so spans of futures
+    /// are named after the spans entered while they are polled. Spans leave the chain when
+    /// they're exited, also while unwinding from a panic.
+    pub fn breadcrumbs(mut self, enabled: bool) -> Self {
+        self.breadcrumbs = enabled;
+        self
+    }
+
+    /// Emit an event every time a span is entered or exited, off by default.
+    ///
+    /// The events have the name and signpost ID of the span interval, with an `enter` or
+    /// `exit` message, so they show up within its lane. For spans of instrumented futures
+    /// they mark every poll, which reveals how often a future is polled and the gaps in
+    /// between when debugging stalls. This multiplies the number of signposts.
+    pub fn poll_events(mut self, enabled: bool) -> Self {
+        self.poll_events = enabled;
+        self
+    }
+
+    /// Use the tracing span ID as the signpost ID of span intervals, off by default.
+    ///
+    /// Spans then map to predictable signpost IDs, to correlate tracing logs with
+    /// Instruments traces offline. Span IDs are only unique among the open spans of one
+    /// subscriber, and may be reused once a span closes. Other intervals with the same log
+    /// and name must thus not use IDs that can collide, e.g. from [`SignpostId::next`].
+    /// Loggers with [`SignpostScope::System`] matching keep generating IDs, since span IDs
+    /// aren't unique across processes.
+    pub fn span_ids(mut self, enabled: bool) -> Self {
+        self.span_ids = enabled;
+        self
+    }
+
+    /// Append the tracing IDs of a span and its parent to the begin message, off by default.
+    ///
+    /// The IDs follow the fields as `span=<id> parent=<id>`, in decimal as returned by
+    /// `span::Id::into_u64`, e.g. `user=1 span=3 parent=1`. Root spans have no `parent=`.
+    /// This allows stitching the span tree offline, also where Instruments doesn't show the
+    /// nesting. The format is stable.
+    pub fn include_ids(mut self, enabled: bool) -> Self {
+        self.include_ids = enabled;
+        self
+    }
+
+    /// Only emit a fraction of spans and events, each with probability `rate`.
+    ///
+    /// Spans are sampled when they are created, and only the intervals of sampled spans are
+    /// ended. Events are sampled independently. The rate is clamped to `0.0..=1.0`, and all
+    /// spans and events are emitted by default.
+    pub fn sample_rate(mut self, rate: f64) -> Self {
+        self.sample_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Build the tracing subscriber.
+    pub fn build(self) -> TracingSubscriber {
+        TracingSubscriber {
             intervals: DashMap::new(),
+            log: self.log,
+            levels: self
+                .levels
+                .into_iter()
+                .map(|(level, category)| {
+                    let logger = LevelLogger {
+                        category,
+                        logger: CategoryLogger::new(),
+                    };
+                    (level, logger)
+                })
+                .collect(),
+            name_format: self.name_format,
+            targets: self.targets,
+            public_fields: self.public_fields,
+            busy_intervals: self.busy_intervals,
+            breadcrumbs: self.breadcrumbs,
+            span_ids: self.span_ids,
+            include_ids: self.include_ids,
+            poll_events: self.poll_events,
+            sample_rate: self.sample_rate,
         }
     }
 }
@@ -40,113 +540,783 @@ impl<S> Layer<S> for TracingSubscriber
 where
     S: Subscriber + for<'a> LookupSpan<'a>,
 {
-    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, _ctx: Context<'_, S>) {
-        let logger = global_logger();
-        if !logger.enabled() {
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        if !self.matches_target(attrs.metadata()) {
+            return;
+        }
+
+        let logger = self.logger(attrs.metadata());
+        if !logger.enabled() || !self.sampled() {
             return;
         }
 
         let mut visitor = MessageVisitor::new();
         attrs.record(&mut visitor);
 
-        let name = format!(
-            "{}::{}",
-            attrs.metadata().module_path().unwrap_or_default(),
-            attrs.metadata().name()
-        );
+        let mut name = self
+            .name_format
+            .format(attrs.metadata(), attrs.metadata().name());
+        if self.breadcrumbs {
+            name = breadcrumbs::compose(&name);
+        }
 
-        // Generate unique signpost ID for this span
-        let signpost_id = SignpostId::generate(logger);
+        let signpost_id = self.span_signpost_id(id, logger);
+        let parent = ctx
+            .span(id)
+            .and_then(|span| span.parent())
+            .map(|parent| parent.id());
+        let message = self.begin_message(visitor.finish(), id, parent);
 
-        logger.emit(
+        self.emit(
+            logger,
             signpost_id,
             &name,
-            visitor.message.as_deref(),
-            SignpostType::IntervalBegin,
+            message.as_deref(),
+            SignpostKind::IntervalBegin,
         );
 
         // Store the interval. To be removed when the interval ends.
         self.intervals.insert(
             id.clone(),
             ActiveInterval {
+                logger,
                 id: signpost_id,
                 name,
+                message: None,
+                busy: Vec::new(),
+                error: None,
+                breadcrumbs: Vec::new(),
             },
         );
     }
 
-    fn on_record(&self, _id: &span::Id, _values: &span::Record<'_>, _ctx: Context<'_, S>) {
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, _ctx: Context<'_, S>) {
         // The os_signpost API doesn't have a direct way to add additional data
-        // to an already-started interval.
+        // to an already-started interval, so recorded values go into the end signpost.
+        let mut visitor = MessageVisitor::new();
+        values.record(&mut visitor);
+        let Some(recorded) = visitor.finish() else {
+            return;
+        };
+
+        if let Some(mut interval) = self.intervals.get_mut(id) {
+            match &mut interval.message {
+                Some(message) => {
+                    message.push(' ');
+                    message.push_str(&recorded);
+                }
+                None => interval.message = Some(recorded),
+            }
+        }
+    }
+
+    fn on_follows_from(&self, span: &Id, follows: &Id, _ctx: Context<'_, S>) {
+        // The os_signpost API has no links between intervals, so the causal link is encoded
+        // in the end signpost of the span, like recorded values.
+        self.follows_from(span, follows);
     }
 
     fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
-        let logger = global_logger();
-        if !logger.enabled() {
+        if !self.matches_target(event.metadata()) {
+            return;
+        }
+
+        let logger = self.logger(event.metadata());
+        if !logger.enabled() || !self.sampled() {
             return;
         }
 
         let mut visitor = MessageVisitor::new();
         event.record(&mut visitor);
 
-        let name = format!(
-            "{}::{}",
-            event.metadata().module_path().unwrap_or_default(),
-            ctx.current_span()
-                .metadata()
-                .map(|meta| meta.name())
-                .unwrap_or_default(),
-        );
+        // Errors of events surface in the end signpost of the span they occurred in.
+        if let Some(error) = &visitor.error {
+            let span = ctx.event_span(event);
+            if let Some(mut interval) = span.and_then(|span| self.intervals.get_mut(&span.id())) {
+                interval.error = Some(error.clone());
+            }
+        }
 
-        logger.emit(
+        let name = self.event_name(event, &ctx);
+
+        self.emit(
+            logger,
             SignpostId::generate(logger),
             &name,
-            visitor.message.as_deref(),
-            SignpostType::Event,
+            visitor.finish().as_deref(),
+            SignpostKind::Event,
         );
     }
 
-    fn on_close(&self, id: Id, _ctx: Context<'_, S>) {
-        let logger = global_logger();
-        if !logger.enabled() {
-            return;
+    fn on_enter(&self, id: &Id, _ctx: Context<'_, S>) {
+        if self.busy_intervals {
+            self.enter_busy(id);
+        }
+        if self.breadcrumbs {
+            self.enter_breadcrumb(id);
         }
+        if self.poll_events {
+            self.poll_event(id, "enter");
+        }
+    }
+
+    fn on_exit(&self, id: &Id, _ctx: Context<'_, S>) {
+        if self.poll_events {
+            self.poll_event(id, "exit");
+        }
+        if self.breadcrumbs {
+            self.exit_breadcrumb(id);
+        }
+        if self.busy_intervals {
+            self.exit_busy(id);
+        }
+    }
 
-        // End the interval and remove it from the map.
+    fn on_close(&self, id: Id, _ctx: Context<'_, S>) {
+        // End the interval on the log it began on and remove it from the map.
         if let Some((_, interval)) = self.intervals.remove(&id) {
-            logger.emit(interval.id, &interval.name, None, SignpostType::IntervalEnd);
+            self.end_interval(interval);
         }
     }
 }
 
+impl Drop for TracingSubscriber {
+    fn drop(&mut self) {
+        // Spans still open at shutdown would otherwise never end in Instruments.
+        self.end_active_intervals();
+    }
+}
+
+/// A uniformly distributed number in `0.0..1.0` from a per-thread xorshift generator.
+fn random() -> f64 {
+    thread_local! {
+        static STATE: Cell<u64> = Cell::new(RandomState::new().build_hasher().finish() | 1);
+    }
+
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        // The upper 53 bits of the xorshift64* output fill the f64 mantissa.
+        (x.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11) as f64 / (1u64 << 53) as f64
+    })
+}
+
 /// Extracts message content from tracing span attributes and event fields.
 ///
 /// Messages are extracted from log calls `info!("message")` as well
 /// as annotated proc macros `#[instrument(fields(message = "message"))]`.
+/// All other fields are collected as `key=value` pairs following the message, and an
+/// "error" field last. Errors recorded as `&dyn Error` include their chain of sources.
 struct MessageVisitor {
     /// The captured message content from any "message" field.
     message: Option<String>,
+    /// The remaining fields formatted as space separated `key=value` pairs.
+    fields: String,
+    /// The captured content of any "error" field.
+    error: Option<String>,
 }
 
 impl MessageVisitor {
     /// Creates a new message visitor.
     fn new() -> Self {
-        Self { message: None }
+        Self {
+            message: None,
+            fields: String::new(),
+            error: None,
+        }
+    }
+
+    /// Combines the message, the remaining fields and the error into the signpost message.
+    fn finish(mut self) -> Option<String> {
+        if let Some(error) = self.error.take() {
+            self.push_field("error", &error);
+        }
+        match (self.message, self.fields.is_empty()) {
+            (message, true) => message,
+            (Some(message), false) => Some(format!("{message} {}", self.fields)),
+            (None, false) => Some(self.fields),
+        }
+    }
+
+    fn push_field(&mut self, name: &str, value: &dyn std::fmt::Display) {
+        if !self.fields.is_empty() {
+            self.fields.push(' ');
+        }
+        let _ = write!(self.fields, "{name}={value}");
+    }
+
+    /// Records a field, capturing "message" fields as the message itself.
+    fn record_value(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Display) {
+        match field.name() {
+            "message" => self.message = Some(value.to_string()),
+            "error" => self.error = Some(value.to_string()),
+            name => self.push_field(name, value),
+        }
     }
 }
 
 impl tracing::field::Visit for MessageVisitor {
-    /// Records string field values, capturing only "message" fields.
+    /// Records string field values.
     fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
-        if field.name() == "message" {
-            self.message = Some(value.to_string());
-        }
+        self.record_value(field, &value);
+    }
+
+    /// Records signed integer field values.
+    fn record_i64(&mut self, field: &tracing::field::Field, value: i64) {
+        self.record_value(field, &value);
+    }
+
+    /// Records unsigned integer field values.
+    fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+        self.record_value(field, &value);
     }
 
-    /// Records debug-formattable field values, capturing only "message" fields.
+    /// Records floating point field values.
+    fn record_f64(&mut self, field: &tracing::field::Field, value: f64) {
+        self.record_value(field, &value);
+    }
+
+    /// Records boolean field values.
+    fn record_bool(&mut self, field: &tracing::field::Field, value: bool) {
+        self.record_value(field, &value);
+    }
+
+    /// Records debug-formattable field values.
     fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
-        if field.name() == "message" {
-            self.message = Some(format!("{:?}", value));
+        self.record_value(field, &format_args!("{:?}", value));
+    }
+
+    /// Records errors with their chain of sources, e.g. `outer: inner`.
+    fn record_error(
+        &mut self,
+        field: &tracing::field::Field,
+        value: &(dyn std::error::Error + 'static),
+    ) {
+        let mut chain = value.to_string();
+        let mut source = value.source();
+        while let Some(error) = source {
+            let _ = write!(chain, ": {error}");
+            source = error.source();
+        }
+        self.record_value(field, &chain);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{LazyLock, Mutex};
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Registry;
+
+    /// Captures the messages the visitor extracts from new spans and events.
+    #[derive(Default)]
+    struct CaptureLayer {
+        messages: Mutex<Vec<Option<String>>>,
+    }
+
+    impl<S: Subscriber> Layer<S> for &'static CaptureLayer {
+        fn on_new_span(&self, attrs: &span::Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            let mut visitor = MessageVisitor::new();
+            attrs.record(&mut visitor);
+            self.messages.lock().unwrap().push(visitor.finish());
+        }
+
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            let mut visitor = MessageVisitor::new();
+            event.record(&mut visitor);
+            self.messages.lock().unwrap().push(visitor.finish());
+        }
+    }
+
+    #[test]
+    fn test_name_format() {
+        tracing::subscriber::with_default(Registry::default(), || {
+            let span = tracing::info_span!(target: "my_target", "worker");
+            let metadata = span.metadata().unwrap();
+
+            assert_eq!(NameFormat::SpanOnly.format(metadata, "worker"), "worker");
+            assert_eq!(
+                NameFormat::ModuleAndSpan.format(metadata, "worker"),
+                "signpost::tracing_subscriber::tests::worker"
+            );
+            assert_eq!(
+                NameFormat::TargetAndSpan.format(metadata, "worker"),
+                "my_target::worker"
+            );
+        });
+    }
+
+    #[test]
+    fn test_level_category() {
+        use crate::categories::{DYNAMIC_TRACING, POINTS_OF_INTEREST};
+        let subscriber = TracingSubscriber::builder()
+            .level_category(Level::TRACE, DYNAMIC_TRACING)
+            .level_category(Level::INFO, POINTS_OF_INTEREST)
+            .build();
+        let _guard = crate::Signpost::configure_for_test("test_level_category", DYNAMIC_TRACING);
+
+        tracing::subscriber::with_default(Registry::default(), || {
+            let category = |span: tracing::Span| {
+                let logger = subscriber.logger(span.metadata().unwrap());
+                assert_eq!(logger.subsystem(), "test_level_category");
+                logger.category()
+            };
+
+            assert_eq!(category(tracing::trace_span!("trace")), DYNAMIC_TRACING);
+            assert_eq!(category(tracing::info_span!("info")), POINTS_OF_INTEREST);
+            // Levels without a category use the global logger.
+            assert_eq!(category(tracing::debug_span!("debug")), DYNAMIC_TRACING);
+        });
+    }
+
+    #[test]
+    fn test_level_logger_configured_later() {
+        let subscriber = TracingSubscriber::builder()
+            .level_category(Level::TRACE, crate::categories::DYNAMIC_TRACING)
+            .build();
+        let _guard = crate::Signpost::configure_for_test(
+            "test_level_late",
+            crate::categories::POINTS_OF_INTEREST,
+        );
+        crate::Signpost::reset();
+
+        tracing::subscriber::with_default(Registry::default(), || {
+            let span = tracing::trace_span!("early");
+            let metadata = span.metadata().unwrap();
+            assert_eq!(
+                subscriber.logger(metadata).subsystem(),
+                "signpost.unconfigured"
+            );
+
+            crate::Signpost::configure_or_panic(
+                "test_level_late",
+                crate::categories::POINTS_OF_INTEREST,
+            );
+            let logger = subscriber.logger(metadata);
+            assert_eq!(logger.subsystem(), "test_level_late");
+            assert_eq!(logger.category(), crate::categories::DYNAMIC_TRACING);
+        });
+    }
+
+    /// Captures the signpost names of events.
+    struct EventNameLayer {
+        subscriber: TracingSubscriber,
+        names: Mutex<Vec<String>>,
+    }
+
+    impl<S> Layer<S> for &'static EventNameLayer
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            let name = self.subscriber.event_name(event, &ctx);
+            self.names.lock().unwrap().push(name);
+        }
+    }
+
+    #[test]
+    fn test_event_name() {
+        let layer: &'static EventNameLayer = Box::leak(Box::new(EventNameLayer {
+            subscriber: TracingSubscriber::builder()
+                .name_format(NameFormat::SpanOnly)
+                .build(),
+            names: Mutex::default(),
+        }));
+        let subscriber = Registry::default().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("outside");
+            let outer = tracing::info_span!("outer");
+            let _outer = outer.clone().entered();
+            let _inner = tracing::info_span!("inner").entered();
+            tracing::info!("nested");
+            tracing::info!(parent: &outer, "explicit parent");
+        });
+
+        let names = layer.names.lock().unwrap();
+        assert!(names[0].starts_with("event "), "{}", names[0]);
+        assert_eq!(names[1..], ["inner", "outer"]);
+    }
+
+    /// Inserts an open interval named `span` for the span `id`, as `on_new_span` does.
+    fn open(subscriber: &TracingSubscriber, id: u64) {
+        static LOGGER: LazyLock<OsLog> = LazyLock::new(|| {
+            OsLog::new(
+                "test_tracing".to_string(),
+                crate::categories::POINTS_OF_INTEREST,
+            )
+        });
+        let interval = ActiveInterval {
+            logger: &LOGGER,
+            id: SignpostId::next(),
+            name: "span".to_string(),
+            message: None,
+            busy: Vec::new(),
+            error: None,
+            breadcrumbs: Vec::new(),
+        };
+        subscriber.intervals.insert(Id::from_u64(id), interval);
+    }
+
+    #[test]
+    fn test_active_intervals() {
+        let subscriber = TracingSubscriber::new();
+        open(&subscriber, 1);
+        open(&subscriber, 2);
+        let mut active = subscriber.active_intervals();
+        active.sort_by_key(|(id, _)| id.into_u64());
+        assert_eq!(
+            active,
+            [
+                (Id::from_u64(1), "span".to_string()),
+                (Id::from_u64(2), "span".to_string())
+            ]
+        );
+
+        assert_eq!(subscriber.end_active_intervals(), 2);
+        assert!(subscriber.active_intervals().is_empty());
+        assert!(subscriber.intervals.is_empty());
+        assert_eq!(subscriber.end_active_intervals(), 0);
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_drop_ends_intervals() {
+        let sink = std::sync::Arc::new(crate::MemorySink::new());
+        let log: &'static OsLog = Box::leak(Box::new(
+            OsLog::points_of_interest("test_drop").with_sink(sink.clone()),
+        ));
+        let subscriber = TracingSubscriber::builder()
+            .log(log)
+            .name_format(NameFormat::SpanOnly)
+            .build();
+        let dispatch = tracing::Dispatch::new(Registry::default().with(subscriber));
+
+        tracing::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("open");
+            // Keeps the span open once its handle is dropped.
+            dispatch.clone_span(&span.id().unwrap());
+        });
+        let kinds: Vec<_> = sink.records().iter().map(|record| record.kind).collect();
+        assert_eq!(kinds, [SignpostKind::IntervalBegin]);
+
+        // Dropping the subscriber ends the spans that are still open.
+        drop(dispatch);
+        let records = sink.take();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].id, records[0].id);
+        assert_eq!(records[1].name, "open");
+        assert_eq!(records[1].kind, SignpostKind::IntervalEnd);
+    }
+
+    #[test]
+    fn test_end_message_error() {
+        // Errors of events are appended to the end message.
+        let subscriber = TracingSubscriber::new();
+        open(&subscriber, 5);
+        let mut interval = subscriber.intervals.get_mut(&Id::from_u64(5)).unwrap();
+        assert_eq!(interval.end_message(), None);
+        interval.error = Some("failed".to_string());
+        assert_eq!(interval.end_message().as_deref(), Some("error=failed"));
+        interval.message = Some("rows=1".to_string());
+        assert_eq!(
+            interval.end_message().as_deref(),
+            Some("rows=1 error=failed")
+        );
+        drop(interval);
+        assert_eq!(subscriber.end_active_intervals(), 1);
+    }
+
+    #[test]
+    fn test_follows_from() {
+        // Spans following from another one record its signpost ID.
+        let subscriber = TracingSubscriber::new();
+        open(&subscriber, 6);
+        open(&subscriber, 7);
+        let (first, second) = (Id::from_u64(6), Id::from_u64(7));
+        let first_id = subscriber.intervals.get(&first).unwrap().id;
+        assert!(subscriber.follows_from(&second, &first));
+        assert!(!subscriber.follows_from(&second, &Id::from_u64(8)));
+        assert_eq!(
+            subscriber.intervals.get(&second).unwrap().message,
+            Some(format!("follows_from={first_id}"))
+        );
+        assert!(subscriber.follows_from(&second, &second));
+        assert_eq!(subscriber.end_active_intervals(), 2);
+    }
+
+    #[test]
+    fn test_breadcrumbs() {
+        // Spans created within an entered span are named after it.
+        let subscriber = TracingSubscriber::new();
+        open(&subscriber, 9);
+        let outer = Id::from_u64(9);
+        assert!(subscriber.enter_breadcrumb(&outer));
+        assert_eq!(breadcrumbs::compose("inner"), "span > inner");
+        assert!(subscriber.exit_breadcrumb(&outer));
+        assert!(!subscriber.exit_breadcrumb(&outer));
+        assert!(!subscriber.enter_breadcrumb(&Id::from_u64(10)));
+        assert_eq!(breadcrumbs::compose("inner"), "inner");
+        assert_eq!(subscriber.end_active_intervals(), 1);
+    }
+
+    #[test]
+    fn test_busy_intervals() {
+        // Busy intervals nest within the span interval.
+        let subscriber = TracingSubscriber::builder().busy_intervals(true).build();
+        let id = Id::from_u64(4);
+        assert!(!subscriber.enter_busy(&id));
+        open(&subscriber, 4);
+        assert!(subscriber.enter_busy(&id));
+        assert!(subscriber.enter_busy(&id));
+        assert!(subscriber.exit_busy(&id));
+        assert_eq!(subscriber.intervals.get(&id).unwrap().busy.len(), 1);
+        assert!(subscriber.exit_busy(&id));
+        assert!(!subscriber.exit_busy(&id));
+    }
+
+    #[test]
+    fn test_sample_rate() {
+        let sampled = |rate| {
+            let subscriber = TracingSubscriber::builder().sample_rate(rate).build();
+            (0..10_000).filter(|_| subscriber.sampled()).count()
+        };
+
+        assert_eq!(sampled(0.0), 0);
+        assert_eq!(sampled(-1.0), 0);
+        assert_eq!(sampled(1.0), 10_000);
+        assert!((4_000..6_000).contains(&sampled(0.5)));
+        assert!((0.0..1.0).contains(&random()));
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_poll_events() {
+        let sink = std::sync::Arc::new(crate::MemorySink::new());
+        let logger: &'static OsLog = Box::leak(Box::new(
+            OsLog::points_of_interest("test_poll").with_sink(sink.clone()),
+        ));
+        let subscriber = TracingSubscriber::builder().poll_events(true).build();
+        let (span, signpost_id) = (Id::from_u64(1), SignpostId::next());
+        subscriber.intervals.insert(
+            span.clone(),
+            ActiveInterval {
+                logger,
+                id: signpost_id,
+                name: "poll".to_string(),
+                message: None,
+                busy: Vec::new(),
+                error: None,
+                breadcrumbs: Vec::new(),
+            },
+        );
+
+        assert!(subscriber.poll_event(&span, "enter"));
+        assert!(subscriber.poll_event(&span, "exit"));
+        assert!(!subscriber.poll_event(&Id::from_u64(2), "enter"));
+        let events: Vec<_> = sink
+            .take()
+            .into_iter()
+            .map(|record| (record.id, record.name, record.message, record.kind))
+            .collect();
+        let event = |message: &str| {
+            let message = Some(message.to_string());
+            (
+                signpost_id,
+                "poll".to_string(),
+                message,
+                SignpostKind::Event,
+            )
+        };
+        assert_eq!(events, [event("enter"), event("exit")]);
+        subscriber.intervals.clear();
+    }
+
+    #[test]
+    fn test_span_ids() {
+        let log = OsLog::new(
+            "test_tracing".to_string(),
+            crate::categories::POINTS_OF_INTEREST,
+        );
+        let subscriber = TracingSubscriber::builder().span_ids(true).build();
+        let id = subscriber.span_signpost_id(&Id::from_u64(42), &log);
+        assert_eq!(id.raw(), 42);
+        let id = subscriber.span_signpost_id(&Id::from_u64(u64::MAX), &log);
+        assert_eq!(id.raw(), u64::MAX - 1);
+
+        // System wide matching and the default keep generating IDs.
+        let system = log.clone().with_scope(SignpostScope::System);
+        let generated = |subscriber: &TracingSubscriber, log| {
+            let span = Id::from_u64(42);
+            let first = subscriber.span_signpost_id(&span, log);
+            let second = subscriber.span_signpost_id(&span, log);
+            assert_ne!(first, second);
+        };
+        generated(&subscriber, &system);
+        generated(&TracingSubscriber::new(), &log);
+    }
+
+    #[test]
+    #[cfg(not(feature = "disabled"))]
+    fn test_span_ids_emitted() {
+        let sink = std::sync::Arc::new(crate::MemorySink::new());
+        let log: &'static OsLog = Box::leak(Box::new(
+            OsLog::points_of_interest("test_span_ids").with_sink(sink.clone()),
+        ));
+        let subscriber = TracingSubscriber::builder()
+            .log(log)
+            .name_format(NameFormat::SpanOnly)
+            .span_ids(true)
+            .build();
+
+        let spans = tracing::subscriber::with_default(Registry::default().with(subscriber), || {
+            let outer = tracing::info_span!("outer");
+            let inner = tracing::info_span!(parent: &outer, "inner");
+            let ids = [outer.id().unwrap(), inner.id().unwrap()];
+            drop(inner);
+            drop(outer);
+            ids.map(|id| id.into_u64())
+        });
+
+        let records: Vec<_> = sink
+            .take()
+            .into_iter()
+            .map(|record| (record.id.raw(), record.name, record.kind))
+            .collect();
+        let record = |id, name: &str, kind| (id, name.to_string(), kind);
+        assert_eq!(
+            records,
+            [
+                record(spans[0], "outer", SignpostKind::IntervalBegin),
+                record(spans[1], "inner", SignpostKind::IntervalBegin),
+                record(spans[1], "inner", SignpostKind::IntervalEnd),
+                record(spans[0], "outer", SignpostKind::IntervalEnd),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_include_ids() {
+        let subscriber = TracingSubscriber::builder().include_ids(true).build();
+        let (id, parent) = (Id::from_u64(3), Some(Id::from_u64(1)));
+        assert_eq!(
+            subscriber.begin_message(Some("user=1".to_string()), &id, parent.clone()),
+            Some("user=1 span=3 parent=1".to_string())
+        );
+        assert_eq!(
+            subscriber.begin_message(None, &id, None),
+            Some("span=3".to_string())
+        );
+
+        let fields = Some("user=1".to_string());
+        let default = TracingSubscriber::new();
+        assert_eq!(default.begin_message(fields.clone(), &id, parent), fields);
+    }
+
+    #[test]
+    fn test_public_fields() {
+        assert_eq!(TracingSubscriber::new().message_format(), c"%{public}s");
+        let subscriber = TracingSubscriber::builder().public_fields(false).build();
+        assert_eq!(subscriber.message_format(), c"%{private}s");
+    }
+
+    #[test]
+    fn test_target_filter() {
+        let subscriber = TracingSubscriber::builder()
+            .with_targets(["my_crate::hotpath", "other"])
+            .build();
+
+        tracing::subscriber::with_default(Registry::default(), || {
+            let matches = |span: tracing::Span| subscriber.matches_target(span.metadata().unwrap());
+
+            assert!(matches(
+                tracing::info_span!(target: "my_crate::hotpath", "a")
+            ));
+            assert!(matches(
+                tracing::info_span!(target: "my_crate::hotpath::inner", "b")
+            ));
+            assert!(matches(tracing::info_span!(target: "other", "c")));
+            assert!(!matches(
+                tracing::info_span!(target: "my_crate::hotpath_other", "d")
+            ));
+            assert!(!matches(tracing::info_span!(target: "my_crate", "e")));
+            assert!(TracingSubscriber::new()
+                .matches_target(tracing::info_span!(target: "any", "f").metadata().unwrap()));
+        });
+    }
+
+    #[test]
+    fn test_message_visitor_fields() {
+        let layer: &'static CaptureLayer = Box::leak(Box::default());
+        let subscriber = Registry::default().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("span", value = 42, ratio = 0.5, flag = true).entered();
+            let _span = tracing::info_span!("message", message = "text", name = "x").entered();
+            let _span = tracing::info_span!("empty").entered();
+            tracing::info!(result = -1, "done {}", 1);
+        });
+
+        assert_eq!(
+            *layer.messages.lock().unwrap(),
+            vec![
+                Some("value=42 ratio=0.5 flag=true".to_string()),
+                Some("text name=x".to_string()),
+                None,
+                Some("done 1 result=-1".to_string()),
+            ]
+        );
+    }
+
+    #[derive(Debug)]
+    struct Error {
+        message: &'static str,
+        source: Option<Box<Error>>,
+    }
+
+    impl std::fmt::Display for Error {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.message)
         }
     }
+
+    impl std::error::Error for Error {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            self.source.as_deref().map(|source| source as _)
+        }
+    }
+
+    #[test]
+    fn test_message_visitor_error() {
+        let layer: &'static CaptureLayer = Box::leak(Box::default());
+        let subscriber = Registry::default().with(layer);
+        let error = Error {
+            message: "request failed",
+            source: Some(Box::new(Error {
+                message: "connection reset",
+                source: None,
+            })),
+        };
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!(error = %error, "display");
+            tracing::error!(error = ?error.source.as_ref().unwrap().message);
+            tracing::error!(error = &error as &dyn std::error::Error, retries = 3);
+            tracing::error!(cause = &error as &dyn std::error::Error);
+        });
+
+        assert_eq!(
+            *layer.messages.lock().unwrap(),
+            vec![
+                Some("display error=request failed".to_string()),
+                Some("error=\"connection reset\"".to_string()),
+                Some("retries=3 error=request failed: connection reset".to_string()),
+                Some("cause=request failed: connection reset".to_string()),
+            ]
+        );
+    }
 }
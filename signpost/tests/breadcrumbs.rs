@@ -0,0 +1,71 @@
+//! Tests for naming nested intervals, in their own process since the setting is global.
+#![cfg(all(feature = "test-util", not(feature = "disabled")))]
+
+use std::sync::Arc;
+
+use signpost::{categories, MemorySink, OsLog, Signpost, SignpostId, SignpostKind};
+
+#[test]
+fn test_breadcrumbs() {
+    let sink = Arc::new(MemorySink::new());
+    let log = OsLog::new(
+        "test_breadcrumbs".to_string(),
+        categories::POINTS_OF_INTEREST,
+    )
+    .with_sink(sink.clone());
+    let names = || -> Vec<(SignpostKind, String)> {
+        sink.take()
+            .into_iter()
+            .map(|record| (record.kind, record.name))
+            .collect()
+    };
+
+    Signpost::set_breadcrumbs(true);
+    {
+        let _outer = log.interval(SignpostId::next(), "outer");
+        let inner = log.interval(SignpostId::next(), "inner");
+        drop(log.interval(SignpostId::next(), "leaf"));
+        inner.end();
+        drop(log.interval(SignpostId::next(), "sibling"));
+    }
+    assert_eq!(
+        names(),
+        [
+            (SignpostKind::IntervalBegin, "outer".to_string()),
+            (SignpostKind::IntervalBegin, "outer > inner".to_string()),
+            (
+                SignpostKind::IntervalBegin,
+                "outer > inner > leaf".to_string()
+            ),
+            (
+                SignpostKind::IntervalEnd,
+                "outer > inner > leaf".to_string()
+            ),
+            (SignpostKind::IntervalEnd, "outer > inner".to_string()),
+            (SignpostKind::IntervalBegin, "outer > sibling".to_string()),
+            (SignpostKind::IntervalEnd, "outer > sibling".to_string()),
+            (SignpostKind::IntervalEnd, "outer".to_string()),
+        ]
+    );
+
+    // Intervals leave the chain when they end while unwinding.
+    let panicked = std::panic::catch_unwind(|| {
+        let _interval = log.interval(SignpostId::next(), "panics");
+        panic!("failed");
+    });
+    assert!(panicked.is_err());
+    sink.take();
+    drop(log.interval(SignpostId::next(), "after"));
+    assert_eq!(
+        names()[0],
+        (SignpostKind::IntervalBegin, "after".to_string())
+    );
+
+    Signpost::set_breadcrumbs(false);
+    let _outer = log.interval(SignpostId::next(), "outer");
+    drop(log.interval(SignpostId::next(), "inner"));
+    assert_eq!(
+        names()[1],
+        (SignpostKind::IntervalBegin, "inner".to_string())
+    );
+}
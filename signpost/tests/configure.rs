@@ -0,0 +1,15 @@
+//! Tests for configuring the process, in their own process since configuration is global.
+
+use signpost::{categories, global_logger, SignpostError};
+
+#[test]
+fn test_configure_from_crate() {
+    signpost::configure_from_crate!().unwrap();
+    assert_eq!(global_logger().subsystem(), env!("CARGO_PKG_NAME"));
+    assert_eq!(global_logger().category(), categories::POINTS_OF_INTEREST);
+
+    assert_eq!(
+        signpost::configure_from_crate!(categories::DYNAMIC_TRACING).err(),
+        Some(SignpostError::AlreadyConfigured)
+    );
+}
@@ -0,0 +1,411 @@
+//! Tests for the `#[signpost]` attribute macro.
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::task::{Context, Poll, Waker};
+
+use signpost::{categories, signpost, OsLog, Signpost};
+
+/// Polls a future that completes without waiting to completion.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn configure() {
+    // Tests share the process, so only the first call configures it.
+    let _ = Signpost::configure("test_derive", categories::POINTS_OF_INTEREST);
+}
+
+#[test]
+fn test_arguments() {
+    configure();
+
+    #[signpost]
+    fn plain() -> u32 {
+        1
+    }
+
+    #[signpost(name = "custom name")]
+    fn named() -> u32 {
+        2
+    }
+
+    #[signpost(name = "custom name", message = "custom message")]
+    fn named_with_message() -> u32 {
+        3
+    }
+
+    #[signpost("custom message", name = "custom name")]
+    fn message_with_name() -> u32 {
+        4
+    }
+
+    assert_eq!(
+        plain() + named() + named_with_message() + message_with_name(),
+        10
+    );
+}
+
+#[test]
+fn test_logger_selection() {
+    configure();
+
+    static LOG: OnceLock<OsLog> = OnceLock::new();
+    fn log() -> &'static OsLog {
+        LOG.get_or_init(|| {
+            OsLog::new(
+                "test_derive.custom".to_string(),
+                categories::DYNAMIC_TRACING,
+            )
+        })
+    }
+
+    #[signpost(category = categories::DYNAMIC_TRACING)]
+    fn dynamic_tracing() -> u32 {
+        1
+    }
+
+    #[signpost(category = signpost::categories::DYNAMIC_TRACING, name = "custom name")]
+    async fn async_dynamic_tracing() -> u32 {
+        2
+    }
+
+    #[signpost(log = log())]
+    fn custom_log() -> u32 {
+        3
+    }
+
+    assert_eq!(
+        dynamic_tracing() + block_on(async_dynamic_tracing()) + custom_log(),
+        6
+    );
+    assert!(std::ptr::eq(
+        signpost::category_logger(categories::DYNAMIC_TRACING),
+        signpost::category_logger(categories::DYNAMIC_TRACING)
+    ));
+}
+
+#[test]
+fn test_skip_if() {
+    configure();
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    fn skip() -> bool {
+        CALLS.fetch_add(1, Ordering::Relaxed) % 2 == 0
+    }
+
+    #[signpost(skip_if = skip, message = "sampled")]
+    fn sampled() -> u32 {
+        1
+    }
+
+    #[signpost(skip_if = skip)]
+    async fn async_sampled() -> u32 {
+        2
+    }
+
+    assert_eq!(sampled() + block_on(async_sampled()), 3);
+
+    // The predicate is only evaluated while signposts are enabled.
+    let enabled = signpost::global_logger().enabled();
+    assert_eq!(CALLS.load(Ordering::Relaxed) > 0, enabled);
+}
+
+#[test]
+fn test_record_result() {
+    configure();
+
+    fn parse(value: &str) -> Result<u32, std::num::ParseIntError> {
+        value.parse()
+    }
+
+    #[signpost(record_result)]
+    fn sum(a: &str, b: &str) -> Result<u32, std::num::ParseIntError> {
+        let a = parse(a)?;
+        if a == 0 {
+            return Ok(0);
+        }
+        Ok(a + parse(b)?)
+    }
+
+    #[signpost(record_result, skip_if = || false, name = "async sum")]
+    async fn async_sum(a: &str, b: &str) -> Result<u32, std::num::ParseIntError> {
+        Ok(parse(a)? + parse(b)?)
+    }
+
+    assert_eq!(sum("1", "2"), Ok(3));
+    assert_eq!(sum("0", "x"), Ok(0));
+    assert!(sum("1", "x").is_err());
+    assert_eq!(block_on(async_sum("1", "2")), Ok(3));
+    assert!(block_on(async_sum("x", "2")).is_err());
+}
+
+#[test]
+fn test_methods() {
+    configure();
+
+    struct Foo(u32);
+    struct Baz(u32);
+
+    impl Foo {
+        #[signpost]
+        fn bar(&self) -> u32 {
+            self.0
+        }
+    }
+
+    impl Baz {
+        // Named after `Baz` instead of the module, so it is distinct from `Foo::bar`.
+        #[signpost]
+        fn bar(&self) -> u32 {
+            self.0
+        }
+
+        #[signpost(message = "async method")]
+        async fn async_bar(self) -> u32 {
+            self.0
+        }
+    }
+
+    assert_eq!(
+        Foo(1).bar() + Baz(2).bar() + block_on(Baz(3).async_bar()),
+        6
+    );
+}
+
+#[test]
+fn test_future_return() {
+    configure();
+
+    #[signpost]
+    fn impl_future(value: u32) -> impl Future<Output = u32> {
+        async move { value }
+    }
+
+    #[signpost(name = "boxed")]
+    fn boxed_future(value: u32) -> std::pin::Pin<Box<dyn Future<Output = u32> + Send>> {
+        if value == 0 {
+            return Box::pin(async { 0 });
+        }
+        Box::pin(async move { value })
+    }
+
+    #[signpost(record_result)]
+    fn result_future(value: &str) -> impl Future<Output = Result<u32, std::num::ParseIntError>> {
+        let value = value.to_string();
+        async move { value.parse() }
+    }
+
+    assert_eq!(block_on(impl_future(1)), 1);
+    assert_eq!(block_on(boxed_future(2)) + block_on(boxed_future(0)), 2);
+    assert_eq!(block_on(result_future("3")), Ok(3));
+}
+
+#[test]
+fn test_panic() {
+    configure();
+
+    #[signpost]
+    fn panics(value: u32) -> u32 {
+        if value == 0 {
+            panic!("zero");
+        }
+        value
+    }
+
+    assert!(std::panic::catch_unwind(|| panics(0)).is_err());
+    assert_eq!(panics(1), 1);
+}
+
+#[test]
+fn test_async_inference() {
+    configure();
+
+    #[signpost]
+    async fn parse(value: &str) -> Result<u32, Box<dyn std::error::Error>> {
+        Ok(value.parse::<u32>()?)
+    }
+
+    assert_eq!(block_on(parse("1")).unwrap(), 1);
+    assert!(block_on(parse("x")).is_err());
+}
+
+#[test]
+fn test_fields() {
+    configure();
+
+    static EVALUATED: AtomicUsize = AtomicUsize::new(0);
+    fn evaluated(value: usize) -> usize {
+        EVALUATED.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    struct User {
+        id: u32,
+    }
+
+    #[signpost(fields(user_id = user.id, size = evaluated(buf.len())))]
+    fn upload(user: &User, buf: Vec<u8>) -> usize {
+        buf.len()
+    }
+
+    #[signpost("async upload", fields(size = evaluated(buf.len())))]
+    async fn async_upload(buf: Vec<u8>) -> usize {
+        buf.len()
+    }
+
+    assert_eq!(upload(&User { id: 1 }, vec![0; 2]), 2);
+    assert_eq!(block_on(async_upload(vec![0; 3])), 3);
+
+    // The fields are only evaluated while signposts are enabled.
+    let enabled = signpost::current_logger().enabled();
+    assert_eq!(EVALUATED.load(Ordering::Relaxed) > 0, enabled);
+}
+
+#[test]
+#[cfg(all(feature = "test-util", not(feature = "disabled")))]
+fn test_fields_message() {
+    use signpost::{MemorySink, SignpostKind};
+    use std::sync::Arc;
+
+    static SINK: OnceLock<Arc<MemorySink>> = OnceLock::new();
+    static LOG: OnceLock<OsLog> = OnceLock::new();
+    fn log() -> &'static OsLog {
+        let sink = SINK.get_or_init(Default::default).clone();
+        LOG.get_or_init(|| OsLog::points_of_interest("test_derive.fields").with_sink(sink))
+    }
+
+    #[signpost(log = log(), name = "fields", fields(a = 1 + 1, b = "text"))]
+    fn fields() {}
+
+    #[signpost(log = log(), name = "message", message = "{literal}", fields(c = [1, 2]))]
+    fn message() {}
+
+    fields();
+    message();
+
+    let begins: Vec<_> = SINK
+        .get()
+        .unwrap()
+        .take()
+        .into_iter()
+        .filter(|record| record.kind == SignpostKind::IntervalBegin)
+        .map(|record| (record.name, record.message.unwrap()))
+        .collect();
+    assert_eq!(
+        begins,
+        [
+            ("fields".to_string(), "a=2 b=\"text\"".to_string()),
+            ("message".to_string(), "{literal} c=[1, 2]".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_on_error() {
+    configure();
+
+    #[signpost(on_error = "event")]
+    fn parse(value: &str) -> Result<u32, std::num::ParseIntError> {
+        value.parse()
+    }
+
+    #[signpost(on_error = "event", record_result)]
+    async fn async_parse(value: &str) -> std::io::Result<u32> {
+        value
+            .parse()
+            .map_err(|_| std::io::Error::other("invalid number"))
+    }
+
+    #[signpost(on_error = "event")]
+    fn parse_later(value: &str) -> impl Future<Output = Result<u32, std::num::ParseIntError>> {
+        let value = value.to_string();
+        async move { value.parse() }
+    }
+
+    assert_eq!(parse("1"), Ok(1));
+    assert!(parse("x").is_err());
+    assert_eq!(block_on(async_parse("2")).unwrap(), 2);
+    assert!(block_on(async_parse("x")).is_err());
+    assert_eq!(block_on(parse_later("3")), Ok(3));
+}
+
+#[test]
+#[cfg(all(feature = "test-util", not(feature = "disabled")))]
+fn test_cancellation() {
+    use signpost::{MemorySink, SignpostKind};
+    use std::sync::Arc;
+
+    static SINK: OnceLock<Arc<MemorySink>> = OnceLock::new();
+    static LOG: OnceLock<OsLog> = OnceLock::new();
+    fn log() -> &'static OsLog {
+        let sink = SINK.get_or_init(Default::default).clone();
+        LOG.get_or_init(|| OsLog::points_of_interest("test_derive.cancel").with_sink(sink))
+    }
+
+    /// A future that is pending once before completing.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if std::mem::replace(&mut self.0, true) {
+                return Poll::Ready(());
+            }
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    #[signpost(log = log(), name = "work")]
+    async fn work(early: bool) -> u32 {
+        if early {
+            return 0;
+        }
+        YieldOnce(false).await;
+        1
+    }
+
+    #[signpost(log = log(), name = "skippable", skip_if = || false)]
+    fn skippable() -> impl Future<Output = u32> {
+        async {
+            YieldOnce(false).await;
+            2
+        }
+    }
+
+    let end_messages = || -> Vec<Option<String>> {
+        SINK.get()
+            .unwrap()
+            .take()
+            .into_iter()
+            .filter(|record| record.kind == SignpostKind::IntervalEnd)
+            .map(|record| record.message)
+            .collect()
+    };
+
+    assert_eq!(block_on(work(false)), 1);
+    assert_eq!(block_on(work(true)), 0);
+    assert_eq!(block_on(skippable()), 2);
+    assert_eq!(end_messages(), [None, None, None]);
+
+    // Dropped after the first poll, while waiting.
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut future = Box::pin(work(false));
+    assert!(future.as_mut().poll(&mut cx).is_pending());
+    drop(future);
+    let mut future = Box::pin(skippable());
+    assert!(future.as_mut().poll(&mut cx).is_pending());
+    drop(future);
+    let cancelled = Some("cancelled".to_string());
+    assert_eq!(end_messages(), [cancelled.clone(), cancelled]);
+}
@@ -0,0 +1,80 @@
+//! Tests for limiting the interval depth, in their own process since the limit is global.
+#![cfg(all(feature = "test-util", not(feature = "disabled")))]
+
+use std::sync::{Arc, OnceLock};
+
+use signpost::{
+    categories, interval_with_message, signpost, MemorySink, OsLog, Signpost, SignpostId,
+    SignpostKind,
+};
+
+static SINK: OnceLock<Arc<MemorySink>> = OnceLock::new();
+static LOG: OnceLock<OsLog> = OnceLock::new();
+
+fn log() -> &'static OsLog {
+    let sink = SINK.get_or_init(Default::default).clone();
+    LOG.get_or_init(|| {
+        OsLog::new("test_max_depth".to_string(), categories::POINTS_OF_INTEREST).with_sink(sink)
+    })
+}
+
+fn kinds() -> Vec<SignpostKind> {
+    SINK.get()
+        .unwrap()
+        .take()
+        .into_iter()
+        .map(|record| record.kind)
+        .collect()
+}
+
+#[test]
+fn test_max_interval_depth() {
+    #[signpost(log = log(), name = "fib")]
+    fn fib(n: u64) -> u64 {
+        if n < 2 {
+            n
+        } else {
+            fib(n - 1) + fib(n - 2)
+        }
+    }
+
+    Signpost::set_max_interval_depth(2);
+    assert_eq!(fib(10), 55);
+    // The first call and the two calls it makes.
+    let begins = |kinds: &[SignpostKind]| {
+        let begins = kinds
+            .iter()
+            .filter(|kind| **kind == SignpostKind::IntervalBegin);
+        begins.count()
+    };
+    let recorded = kinds();
+    assert_eq!(begins(&recorded), 3);
+    assert_eq!(recorded.len(), 6);
+
+    // Suppressed intervals kept the depth balanced.
+    let outer = log().interval(SignpostId::next(), "outer");
+    drop(log().interval(SignpostId::next(), "inner"));
+    drop(log().interval(SignpostId::next(), "inner"));
+    let inner = log().interval(SignpostId::next(), "inner");
+    drop(log().interval(SignpostId::next(), "suppressed"));
+    drop(inner);
+    drop(outer);
+    assert_eq!(begins(&kinds()), 4);
+
+    // Intervals that didn't begin because signposts were disabled still count.
+    let sink = SINK.get().unwrap();
+    sink.set_enabled(false);
+    let outer = interval_with_message!(log(), "outer", "message");
+    let inner = interval_with_message!(log(), "inner", "message");
+    sink.set_enabled(true);
+    drop(interval_with_message!(log(), "suppressed", "message"));
+    drop(inner);
+    drop(outer);
+    assert_eq!(begins(&kinds()), 0);
+    drop(interval_with_message!(log(), "interval", "message"));
+    assert_eq!(begins(&kinds()), 1);
+
+    Signpost::set_max_interval_depth(usize::MAX);
+    assert_eq!(fib(3), 2);
+    assert_eq!(begins(&kinds()), 5);
+}
@@ -0,0 +1,36 @@
+//! Tests for the process-wide kill switch, in their own process since it affects every test.
+
+use signpost::{categories, OsLog, Signpost, SignpostId};
+
+#[test]
+fn test_set_enabled() {
+    let log = OsLog::new(
+        "test_set_enabled".to_string(),
+        categories::POINTS_OF_INTEREST,
+    );
+    assert!(Signpost::is_enabled());
+
+    Signpost::set_enabled(false);
+    assert!(!Signpost::is_enabled());
+    assert!(!log.enabled());
+    assert!(!signpost::global_logger().enabled());
+    log.event(SignpostId::next(), "event");
+
+    #[cfg(all(feature = "test-util", not(feature = "disabled")))]
+    {
+        use std::sync::Arc;
+
+        let sink = Arc::new(signpost::MemorySink::new());
+        let log = log.clone().with_sink(sink.clone());
+        drop(log.interval(SignpostId::next(), "interval"));
+        assert!(sink.take().is_empty());
+
+        Signpost::set_enabled(true);
+        assert!(log.enabled());
+        log.event(SignpostId::next(), "event");
+        assert_eq!(sink.take().len(), 1);
+    }
+
+    Signpost::set_enabled(true);
+    assert!(Signpost::is_enabled());
+}
@@ -0,0 +1,8 @@
+//! Compile tests for the `#[signpost]` attribute macro.
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/fail/*.rs");
+}
@@ -0,0 +1,20 @@
+#![deny(unused_must_use)]
+
+use signpost::signpost;
+
+#[signpost]
+#[must_use]
+fn value() -> u32 {
+    0
+}
+
+#[signpost(record_result)]
+#[must_use = "check the result"]
+fn checked() -> Result<u32, ()> {
+    Ok(0)
+}
+
+fn main() {
+    value();
+    checked();
+}
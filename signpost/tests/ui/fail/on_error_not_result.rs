@@ -0,0 +1,21 @@
+use signpost::signpost;
+
+#[signpost(on_error = "event")]
+fn no_return() {}
+
+#[signpost(on_error = "event")]
+fn number() -> u32 {
+    1
+}
+
+#[signpost(on_error = "event")]
+async fn async_number() -> u32 {
+    1
+}
+
+#[signpost(on_error = "event")]
+fn future_number() -> impl std::future::Future<Output = u32> {
+    async { 1 }
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use signpost::signpost;
+
+#[signpost(on_error = "interval")]
+fn interval() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use signpost::signpost;
+
+#[signpost(record_result)]
+#[track_caller]
+fn parse(value: &str) -> Result<u32, std::num::ParseIntError> {
+    value.parse()
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+use std::fmt::Display;
+
+use signpost::signpost;
+
+#[signpost]
+async fn display(value: u32) -> impl Display {
+    value
+}
+
+#[signpost(record_result)]
+async fn fallible(value: u32) -> Result<impl Display, std::fmt::Error> {
+    Ok(value)
+}
+
+fn assert_display<T: Display>(_: &T) {}
+
+async fn caller() {
+    let value = display(1).await;
+    assert_display(&value);
+    let value = fallible(2).await.unwrap();
+    assert_display(&value);
+}
+
+fn main() {
+    let _ = caller();
+}
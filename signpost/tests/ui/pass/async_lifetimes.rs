@@ -0,0 +1,30 @@
+use signpost::signpost;
+
+#[signpost]
+async fn first<'a>(value: &'a str) -> &'a str {
+    value.split(' ').next().unwrap_or(value)
+}
+
+#[signpost(message = "longest")]
+async fn longest<'a, 'b: 'a>(a: &'a str, b: &'b str) -> &'a str {
+    if a.len() >= b.len() {
+        a
+    } else {
+        b
+    }
+}
+
+struct Words<'a>(&'a str);
+
+impl<'a> Words<'a> {
+    #[signpost]
+    async fn first(&self) -> &'a str {
+        first(self.0).await
+    }
+}
+
+fn main() {
+    let words = Words("a b");
+    let _ = words.first();
+    let _ = longest("a", "b");
+}
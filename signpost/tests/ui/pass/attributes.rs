@@ -0,0 +1,38 @@
+use std::panic::Location;
+
+use signpost::signpost;
+
+#[signpost]
+#[inline]
+fn inlined(value: u32) -> u32 {
+    value + 1
+}
+
+#[signpost]
+#[inline(always)]
+async fn inlined_async(value: u32) -> u32 {
+    value + 1
+}
+
+#[signpost]
+#[cold]
+#[must_use]
+fn cold() -> u32 {
+    0
+}
+
+#[signpost(name = "caller")]
+#[track_caller]
+fn caller() -> &'static Location<'static> {
+    Location::caller()
+}
+
+fn main() {
+    let _ = inlined(cold());
+    let _ = inlined_async(0);
+
+    // The location of the call, not of the function body.
+    let line = line!() + 1;
+    let location = caller();
+    assert_eq!((location.file(), location.line()), (file!(), line));
+}
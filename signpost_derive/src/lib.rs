@@ -9,7 +9,10 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
     parse::{Parse, ParseStream},
-    parse_macro_input, Expr, ExprLit, ItemFn, Lit, LitStr, Meta, MetaNameValue, Result,
+    parse_macro_input,
+    punctuated::Punctuated,
+    Expr, ExprLit, GenericArgument, Ident, ItemFn, Lit, LitStr, Meta, MetaNameValue, PathArguments,
+    PathSegment, Result, ReturnType, Token, Type, TypeParamBound,
 };
 
 /// Automatically instrument a function with signposts
@@ -31,7 +34,67 @@ use syn::{
 /// fn process_data() {
 ///     // Function with custom message
 /// }
+///
+/// #[signpost(name = "Load", message = "Reading config")]
+/// fn load_config() {
+///     // Function with a stable custom name instead of `module::function`
+/// }
+///
+/// #[signpost(category = signpost::categories::DYNAMIC_TRACING)]
+/// fn hot_path() {
+///     // Only recorded while Instruments is recording
+/// }
+///
+/// #[signpost(log = &MY_LOG)]
+/// fn custom_log() {
+///     // Emitted through a user provided `OsLog`
+/// }
+///
+/// #[signpost]
+/// fn spawn_work() -> impl Future<Output = u32> {
+///     // The interval measures the returned future when awaited, also for
+///     // `Pin<Box<dyn Future>>` and `BoxFuture` return types
+/// }
+///
+/// impl Worker {
+///     #[signpost]
+///     fn run(&self) {
+///         // Methods are named `my_crate::Worker::run` after the type of `self`
+///     }
+/// }
+///
+/// #[signpost(record_result)]
+/// async fn fetch() -> Result<Vec<u8>, Error> {
+///     // Emits an `Ok` or `Err(...)` event right before the interval ends
+/// }
+///
+/// #[signpost(on_error = "event")]
+/// fn save() -> Result<(), Error> {
+///     // Emits a `module::save error` event with the error's `Debug` only when returning `Err`
+/// }
+///
+/// #[signpost(fields(user_id = user.id, size = buf.len()))]
+/// fn upload(user: &User, buf: &[u8]) {
+///     // The interval message is `user_id=42 size=1024`, following the message if any.
+///     // Values are formatted with `Debug`, and only evaluated while signposts are enabled.
+///     // For functions returning a future, they are evaluated when it is first polled.
+/// }
+///
+/// #[signpost(skip_if = sampling::skip)]
+/// fn called_often() {
+///     // Skipped whenever `sampling::skip()` returns true, e.g. for 1-in-100 sampling.
+///     // The predicate is only evaluated while signposts are enabled.
+/// }
 /// ```
+///
+/// Async functions and functions returning a future measure the future until it completes.
+/// Futures dropped before completing, e.g. by a timeout, end their interval with a
+/// `cancelled` message.
+///
+/// Other attributes of the function, such as `#[inline]`, `#[cold]`, `#[must_use]` and
+/// `#[track_caller]`, are kept. Since closures don't inherit `#[track_caller]`, it is rejected
+/// with `record_result`, `on_error` and functions returning a future, which wrap the body
+/// in one.
 #[proc_macro_attribute]
 pub fn signpost(args: TokenStream, input: TokenStream) -> TokenStream {
     let args = parse_macro_input!(args as InstrumentArgs);
@@ -43,39 +106,272 @@ pub fn signpost(args: TokenStream, input: TokenStream) -> TokenStream {
     let fn_block = &input_fn.block;
     let fn_attrs = &input_fn.attrs;
 
-    // Generate the signpost message
+    if let Some(on_error) = &args.on_error {
+        let message = "'on_error' requires a 'Result' return type";
+        let error = match (&fn_sig.output, result_type(fn_sig)) {
+            (ReturnType::Default, _) => Some(syn::Error::new_spanned(on_error, message)),
+            (_, Some(ty)) if !is_result(ty) => Some(syn::Error::new_spanned(ty, message)),
+            _ => None,
+        };
+        if let Some(error) = error {
+            return error.to_compile_error().into();
+        }
+    }
+
+    // Bodies are wrapped in a closure to capture their result or early returned future, and
+    // closures don't inherit `#[track_caller]`, so panics would point into the function.
+    let wraps_body = args.record_result
+        || args.on_error.is_some()
+        || (fn_sig.asyncness.is_none() && future_return(&fn_sig.output).is_some());
+    if wraps_body {
+        if let Some(track_caller) = fn_attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("track_caller"))
+        {
+            return syn::Error::new_spanned(
+                track_caller,
+                "'#[track_caller]' is not supported with 'record_result', 'on_error' or \
+                 functions returning a future",
+            )
+            .to_compile_error()
+            .into();
+        }
+    }
+
+    if cfg!(feature = "disabled") {
+        // Keep the argument expressions referenced, so items only they use aren't dead code
+        let referenced = [args.log, args.category, args.skip_if]
+            .into_iter()
+            .flatten()
+            .chain(args.fields.into_iter().map(|field| field.value));
+        return TokenStream::from(quote! {
+            #(#fn_attrs)*
+            #fn_vis #fn_sig {
+                #(let _ = || #referenced;)*
+                #fn_block
+            }
+        });
+    }
+
+    // Methods are named after their type, so methods of the same name on different types
+    // are told apart
+    let fn_path = if fn_sig.receiver().is_some() {
+        quote! { std::any::type_name::<Self>() }
+    } else {
+        quote! { module_path!() }
+    };
+
+    // Generate the signpost name and message
+    let signpost_name = match args.name {
+        Some(name) => quote! { #name },
+        None if cfg!(feature = "source_location") => quote! {
+            &format!("{}::{} ({}:{})", #fn_path, stringify!(#fn_name), file!(), line!())
+        },
+        None => quote! { &format!("{}::{}", #fn_path, stringify!(#fn_name)) },
+    };
     let signpost_message = args.message;
 
+    // Select the logger, caching per-category loggers at the call site
+    let logger_setup = match (args.log, args.category) {
+        (Some(log), _) => quote! {
+            let __logger: &signpost::OsLog = #log;
+        },
+        (None, Some(category)) => quote! {
+            static __LOGGER: signpost::CategoryLogger = signpost::CategoryLogger::new();
+            let __logger = __LOGGER.get(#category);
+        },
+        (None, None) => quote! {
+            let __logger = signpost::current_logger();
+        },
+    };
+
     // Generate common signpost setup
-    let signpost_setup = quote! {
-        let __logger = signpost::global_logger();
-        let __id = signpost::SignpostId::generate(__logger);
+    let signpost_setup = logger_setup;
+
+    // Generate interval creation based on whether message or fields are provided
+    let interval = if !args.fields.is_empty() {
+        let fields_format = args
+            .fields
+            .iter()
+            .map(|field| format!("{}={{:?}}", field.name))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let (format, message) = match signpost_message {
+            Some(message) => (format!("{{}} {fields_format}"), quote! { #message, }),
+            None => (fields_format, quote! {}),
+        };
+        let values = args.fields.iter().map(|field| &field.value);
+        // Only evaluate the fields when signposts are enabled.
+        quote! {
+            if __logger.enabled() {
+                __logger.interval_with_message(
+                    __id,
+                    #signpost_name,
+                    format!(#format, #message #(#values),*),
+                )
+            } else {
+                __logger.interval(__id, #signpost_name)
+            }
+        }
+    } else if let Some(message) = signpost_message {
+        quote! { __logger.interval_with_message(__id, #signpost_name, #message) }
+    } else {
+        quote! { __logger.interval(__id, #signpost_name) }
+    };
+
+    // Intervals of futures are marked as cancelled until they complete, so dropping the future
+    // before completion ends the interval with a "cancelled" message.
+    let asynchronous = fn_sig.asyncness.is_some() || future_return(&fn_sig.output).is_some();
+    let mutability = if asynchronous {
+        quote! { mut }
+    } else {
+        quote! {}
     };
 
-    // Generate interval creation based on whether message is provided
-    let interval_creation = if let Some(message) = signpost_message {
+    let skipped = args.skip_if.is_some();
+    let set_cancellable = |cancellable: bool| {
+        if skipped {
+            quote! {
+                if let Some(__interval) = &mut _interval {
+                    __interval.set_cancellable(#cancellable);
+                }
+            }
+        } else {
+            quote! { _interval.set_cancellable(#cancellable); }
+        }
+    };
+    let (cancellation, completion) = (set_cancellable(true), set_cancellable(false));
+
+    let interval_creation = if let Some(skip_if) = args.skip_if {
+        // Only evaluate the predicate when signposts are enabled.
         quote! {
-            let _interval = __logger.interval_with_message(__id, &format!("{}::{}", module_path!(), stringify!(#fn_name)), #message);
+            let #mutability _interval = if __logger.enabled() && !(#skip_if)() {
+                let __id = signpost::SignpostId::next();
+                Some(#interval)
+            } else {
+                None
+            };
         }
     } else {
         quote! {
-            let _interval = __logger.interval(__id, &format!("{}::{}", module_path!(), stringify!(#fn_name)));
+            let __id = signpost::SignpostId::next();
+            let #mutability _interval = #interval;
         }
     };
 
+    // Emit events describing the result before the interval ends
+    let mut records = Vec::new();
+    if args.record_result {
+        records.push(quote! {
+            let __message = match &__result {
+                Ok(_) => String::from("Ok"),
+                Err(__err) => format!("Err({:?})", __err),
+            };
+            __logger.event_with_message(__interval.id(), #signpost_name, __message);
+        });
+    }
+    if args.on_error.is_some() {
+        records.push(quote! {
+            if let Err(__err) = &__result {
+                __logger.event_with_message(
+                    __interval.id(),
+                    format!("{} error", #signpost_name),
+                    format!("{:?}", __err),
+                );
+            }
+        });
+    }
+    let inspects_result = !records.is_empty();
+    let result_recording = if inspects_result {
+        let record = quote! {
+            if __logger.enabled() {
+                #(#records)*
+            }
+        };
+        if skipped {
+            quote! {
+                if let Some(__interval) = &_interval {
+                    #record
+                }
+            }
+        } else {
+            quote! {
+                let __interval = &_interval;
+                #record
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     // Generate instrumented function
     let instrumented = if fn_sig.asyncness.is_some() {
-        // Handle async functions
+        // Handle async functions, capturing the result of early returns to tell completion
+        // from cancellation. The body is awaited in place, so the returned future keeps the
+        // signature's output type, including `impl Trait` and borrowed lifetimes.
         quote! {
             #(#fn_attrs)*
             #fn_vis #fn_sig {
-                async move {
-                    #signpost_setup
-                    #interval_creation
-                    let __result = async move #fn_block.await;
-                    __result
-                }
-                .await
+                #signpost_setup
+                #interval_creation
+                #cancellation
+                let __result = async move #fn_block.await;
+                #completion
+                #result_recording
+                __result
+            }
+        }
+    } else if let Some(future_return) = future_return(&fn_sig.output) {
+        // Handle functions returning a future, measuring the future instead of its construction
+        let instrumented_future = quote! {
+            async move {
+                #signpost_setup
+                #interval_creation
+                #cancellation
+                let __result = __future.await;
+                #completion
+                #result_recording
+                __result
+            }
+        };
+        // Early returns in the body return the future to be instrumented
+        let (future, instrumented_future) = match future_return {
+            FutureReturn::Impl => (quote! { (|| #fn_block)() }, instrumented_future),
+            FutureReturn::Boxed => {
+                let output = &fn_sig.output;
+                (
+                    quote! { (|| #output #fn_block)() },
+                    quote! { Box::pin(#instrumented_future) },
+                )
+            }
+        };
+        quote! {
+            #(#fn_attrs)*
+            #fn_vis #fn_sig {
+                #[allow(clippy::redundant_closure_call)]
+                let __future = #future;
+                #instrumented_future
+            }
+        }
+    } else if inspects_result {
+        // Handle sync functions, capturing the result of early returns
+        let ReturnType::Type(_, output) = &fn_sig.output else {
+            return syn::Error::new_spanned(
+                fn_sig,
+                "'record_result' requires a 'Result' return type",
+            )
+            .to_compile_error()
+            .into();
+        };
+        quote! {
+            #(#fn_attrs)*
+            #fn_vis #fn_sig {
+                #signpost_setup
+                #interval_creation
+                #[allow(clippy::redundant_closure_call)]
+                let __result = (|| -> #output #fn_block)();
+                #result_recording
+                __result
             }
         }
     } else {
@@ -93,39 +389,233 @@ pub fn signpost(args: TokenStream, input: TokenStream) -> TokenStream {
     TokenStream::from(instrumented)
 }
 
+/// How a function that isn't `async` returns a future.
+enum FutureReturn {
+    /// `impl Future<Output = T>`
+    Impl,
+    /// `Pin<Box<dyn Future<Output = T>>>` or a `BoxFuture<T>` alias of it
+    Boxed,
+}
+
+/// Detects functions returning a future, whose execution should be measured when awaited.
+fn future_return(output: &ReturnType) -> Option<FutureReturn> {
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    match &**ty {
+        Type::ImplTrait(ty) if has_future_bound(&ty.bounds) => Some(FutureReturn::Impl),
+        Type::Path(ty) => {
+            let segment = ty.path.segments.last()?;
+            if segment.ident == "BoxFuture" {
+                return Some(FutureReturn::Boxed);
+            }
+            // Pin<Box<dyn Future>>
+            let boxed = (segment.ident == "Pin")
+                .then(|| generic_type(segment))
+                .flatten()
+                .and_then(|ty| match ty {
+                    Type::Path(ty) => ty.path.segments.last(),
+                    _ => None,
+                })
+                .filter(|segment| segment.ident == "Box")
+                .and_then(generic_type)
+                .is_some_and(
+                    |ty| matches!(ty, Type::TraitObject(ty) if has_future_bound(&ty.bounds)),
+                );
+            boxed.then_some(FutureReturn::Boxed)
+        }
+        _ => None,
+    }
+}
+
+/// The type of the value the function produces: the return type, or the output of a
+/// returned `impl Future`. `None` if it can't be determined from the signature.
+fn result_type(sig: &syn::Signature) -> Option<&Type> {
+    let ReturnType::Type(_, ty) = &sig.output else {
+        return None;
+    };
+    if sig.asyncness.is_some() {
+        return Some(ty);
+    }
+    match future_return(&sig.output) {
+        Some(FutureReturn::Impl) => {
+            let Type::ImplTrait(ty) = &**ty else {
+                return None;
+            };
+            ty.bounds.iter().find_map(|bound| match bound {
+                TypeParamBound::Trait(bound) => {
+                    let PathArguments::AngleBracketed(args) =
+                        &bound.path.segments.last()?.arguments
+                    else {
+                        return None;
+                    };
+                    args.args.iter().find_map(|arg| match arg {
+                        GenericArgument::AssocType(assoc) if assoc.ident == "Output" => {
+                            Some(&assoc.ty)
+                        }
+                        _ => None,
+                    })
+                }
+                _ => None,
+            })
+        }
+        Some(FutureReturn::Boxed) => None,
+        None => Some(ty),
+    }
+}
+
+/// Whether a type is a `Result`, including aliases like `io::Result<T>`.
+fn is_result(ty: &Type) -> bool {
+    match ty {
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident.to_string().ends_with("Result")),
+        Type::Paren(ty) => is_result(&ty.elem),
+        Type::Group(ty) => is_result(&ty.elem),
+        _ => false,
+    }
+}
+
+/// The first generic type argument of a path segment, e.g. `T` of `Box<T>`.
+fn generic_type(segment: &PathSegment) -> Option<&Type> {
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+fn has_future_bound(bounds: &Punctuated<TypeParamBound, Token![+]>) -> bool {
+    bounds.iter().any(|bound| match bound {
+        TypeParamBound::Trait(bound) => bound
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Future"),
+        _ => false,
+    })
+}
+
 struct InstrumentArgs {
+    name: Option<String>,
     message: Option<String>,
+    category: Option<Expr>,
+    log: Option<Expr>,
+    skip_if: Option<Expr>,
+    record_result: bool,
+    on_error: Option<LitStr>,
+    fields: Vec<Field>,
+}
+
+/// A `name = value` pair of the `fields(...)` argument.
+struct Field {
+    name: Ident,
+    value: Expr,
+}
+
+impl Parse for Field {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(Field { name, value })
+    }
 }
 
 impl Parse for InstrumentArgs {
     fn parse(input: ParseStream) -> Result<Self> {
-        let message = if input.is_empty() {
-            None
-        } else if input.peek(LitStr) {
+        let mut args = InstrumentArgs {
+            name: None,
+            message: None,
+            category: None,
+            log: None,
+            skip_if: None,
+            record_result: false,
+            on_error: None,
+            fields: Vec::new(),
+        };
+
+        if input.peek(LitStr) {
             // Parse direct string literal: "message"
-            Some(input.parse::<LitStr>()?.value())
-        } else {
-            // Parse named argument: message = "value"
-            let meta: Meta = input.parse()?;
+            args.message = Some(input.parse::<LitStr>()?.value());
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        // Parse named arguments: name = "value", message = "value", category = path, log = expr,
+        // skip_if = path, on_error = "event", fields(name = expr, ...) and the record_result flag
+        for meta in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
             match meta {
+                Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("name") => {
+                    args.name = Some(string_literal(&value)?);
+                }
                 Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("message") => {
-                    match value {
-                        Expr::Lit(ExprLit {
-                            lit: Lit::Str(lit_str),
-                            ..
-                        }) => Some(lit_str.value()),
-                        _ => return Err(syn::Error::new_spanned(value, "Expected string literal")),
+                    args.message = Some(string_literal(&value)?);
+                }
+                Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("category") => {
+                    args.category = Some(value);
+                }
+                Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("log") => {
+                    args.log = Some(value);
+                }
+                Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("skip_if") => {
+                    args.skip_if = Some(value);
+                }
+                Meta::Path(path) if path.is_ident("record_result") => {
+                    args.record_result = true;
+                }
+                Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("on_error") => {
+                    let Expr::Lit(ExprLit {
+                        lit: Lit::Str(on_error),
+                        ..
+                    }) = &value
+                    else {
+                        return Err(syn::Error::new_spanned(value, "Expected string literal"));
+                    };
+                    if on_error.value() != "event" {
+                        return Err(syn::Error::new_spanned(
+                            on_error,
+                            "Expected 'on_error = \"event\"'",
+                        ));
                     }
+                    args.on_error = Some(on_error.clone());
+                }
+                Meta::List(list) if list.path.is_ident("fields") => {
+                    let fields =
+                        list.parse_args_with(Punctuated::<Field, Token![,]>::parse_terminated)?;
+                    args.fields.extend(fields);
                 }
                 _ => {
                     return Err(syn::Error::new_spanned(
                         meta,
-                        "Expected 'message = \"...\"'",
+                        "Expected 'name = \"...\"', 'message = \"...\"', 'category = ...', 'log = ...', 'skip_if = ...', 'on_error = \"event\"', 'fields(...)' or 'record_result'",
                     ))
                 }
             }
-        };
+        }
+
+        if let (Some(_), Some(category)) = (&args.log, &args.category) {
+            return Err(syn::Error::new_spanned(
+                category,
+                "'category' and 'log' are mutually exclusive",
+            ));
+        }
+
+        Ok(args)
+    }
+}
 
-        Ok(InstrumentArgs { message })
+fn string_literal(value: &Expr) -> Result<String> {
+    match value {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit_str),
+            ..
+        }) => Ok(lit_str.value()),
+        _ => Err(syn::Error::new_spanned(value, "Expected string literal")),
     }
 }